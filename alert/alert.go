@@ -0,0 +1,195 @@
+package alert
+
+import (
+	"fmt"
+	"mindia/breaker"
+	"mindia/folder"
+	"mindia/scheduler"
+	"mindia/storage"
+	"sync"
+	"time"
+)
+
+const (
+	defaultInterval          = time.Minute
+	defaultCapacityThreshold = 0.9
+)
+
+type EventType string
+
+const (
+	EventCapacity     EventType = "capacity"
+	EventDeadLetter   EventType = "dead_letter"
+	EventBreakerOpen  EventType = "breaker_open"
+	EventStorageError EventType = "storage_error"
+)
+
+type Channel string
+
+const (
+	ChannelEmail Channel = "email"
+	ChannelSlack Channel = "slack"
+)
+
+type AlertConfig struct {
+	Smtp            *SmtpConfig `yaml:"smtp,omitempty"`
+	SlackWebhookUrl string      `yaml:"slack_webhook_url,omitempty"`
+
+	// Routes picks the channels of each event type, every configured channel
+	// gets the event types missing from it.
+	Routes map[EventType][]Channel `yaml:"routes,omitempty"`
+
+	// CapacityThreshold is the share of a filesystem storage MaxBytes in use
+	// that raises a capacity alert.
+	CapacityThreshold float64 `yaml:"capacity_threshold,omitempty"`
+
+	Interval time.Duration `yaml:"interval,omitempty"`
+}
+
+type Alert struct {
+	Type    EventType `json:"type"`
+	Subject string    `json:"subject"`
+	Message string    `json:"message"`
+	At      time.Time `json:"at"`
+}
+
+// An Alerter watches the folders every Interval and notifies on the events
+// that appeared since the previous check.
+type Alerter struct {
+	*AlertConfig `yaml:",inline"`
+	folders      func() []*folder.Folder
+	overCapacity map[string]bool
+	deadLetters  map[string]bool
+	trips        map[string]int
+	checkedAt    time.Time
+	mu           sync.Mutex
+}
+
+// folders is called on every check so folders added or removed at runtime are watched.
+func NewAlerter(config *AlertConfig, folders func() []*folder.Folder) *Alerter {
+	if config.CapacityThreshold <= 0 {
+		config.CapacityThreshold = defaultCapacityThreshold
+	}
+	if config.Interval <= 0 {
+		config.Interval = defaultInterval
+	}
+	a := &Alerter{
+		AlertConfig:  config,
+		folders:      folders,
+		overCapacity: map[string]bool{},
+		deadLetters:  map[string]bool{},
+		trips:        map[string]int{},
+		checkedAt:    time.Now(),
+	}
+	go a.watch()
+	return a
+}
+
+func (a *Alerter) watch() {
+	for {
+		time.Sleep(a.Interval)
+		for _, alert := range a.Check() {
+			a.Notify(alert)
+		}
+	}
+}
+
+func (a *Alerter) Check() []*Alert {
+	a.mu.Lock()
+	defer a.mu.Unlock()
+
+	now := time.Now()
+	var alerts []*Alert
+	alerts = append(alerts, a.checkCapacity()...)
+	alerts = append(alerts, a.checkDeadLetters()...)
+	alerts = append(alerts, a.checkBreakers()...)
+	alerts = append(alerts, a.checkStorageErrors()...)
+	for _, alert := range alerts {
+		alert.At = now
+	}
+	a.checkedAt = now
+	return alerts
+}
+
+func (a *Alerter) checkCapacity() []*Alert {
+	var alerts []*Alert
+	for _, f := range a.folders() {
+		fs, ok := f.Storage.(*storage.FilesystemStorage)
+		if !ok || fs.MaxBytes <= 0 {
+			continue
+		}
+		usage, err := fs.Usage()
+		if err != nil {
+			continue
+		}
+		over := float64(usage.UsedBytes) >= a.CapacityThreshold*float64(usage.MaxBytes)
+		if over && !a.overCapacity[usage.MountDir] {
+			alerts = append(alerts, &Alert{
+				Type:    EventCapacity,
+				Subject: fmt.Sprintf("Storage %s is above %.0f%% of its quota", usage.MountDir, a.CapacityThreshold*100),
+				Message: fmt.Sprintf("%d of %d bytes are used.", usage.UsedBytes, usage.MaxBytes),
+			})
+		}
+		a.overCapacity[usage.MountDir] = over
+	}
+	return alerts
+}
+
+func (a *Alerter) checkDeadLetters() []*Alert {
+	seen := map[*scheduler.Scheduler]bool{}
+	deadLetters := map[string]bool{}
+	var alerts []*Alert
+	for _, f := range a.folders() {
+		if f.Scheduler == nil || seen[f.Scheduler] {
+			continue
+		}
+		seen[f.Scheduler] = true
+		for _, t := range f.Scheduler.ReadDeadLetters() {
+			// a requeued task that fails again is a new dead letter
+			key := t.Id + "@" + t.FinishedAt.String()
+			deadLetters[key] = true
+			if a.deadLetters[key] {
+				continue
+			}
+			alerts = append(alerts, &Alert{
+				Type:    EventDeadLetter,
+				Subject: fmt.Sprintf("Task %s %s moved to the dead letter queue", t.Name, t.Group),
+				Message: fmt.Sprintf("Task %s failed after %d attempts: %s", t.Id, t.Attempts, t.Error),
+			})
+		}
+	}
+	a.deadLetters = deadLetters
+	return alerts
+}
+
+func (a *Alerter) checkBreakers() []*Alert {
+	var alerts []*Alert
+	for _, status := range breaker.Statuses() {
+		if status.Trips > a.trips[status.Name] {
+			alerts = append(alerts, &Alert{
+				Type:    EventBreakerOpen,
+				Subject: fmt.Sprintf("Circuit breaker %s opened", status.Name),
+				Message: fmt.Sprintf("%d consecutive failures, last error: %s", status.ConsecutiveFailures, status.LastError),
+			})
+		}
+		a.trips[status.Name] = status.Trips
+	}
+	return alerts
+}
+
+func (a *Alerter) checkStorageErrors() []*Alert {
+	var alerts []*Alert
+	for _, f := range a.folders() {
+		for _, failure := range f.IntegrityReport() {
+			if !failure.LastAt.After(a.checkedAt) {
+				continue
+			}
+			alerts = append(alerts, &Alert{
+				Type:    EventStorageError,
+				Subject: fmt.Sprintf("Storage error on %s/%s", failure.Dir, failure.Name),
+				Message: fmt.Sprintf("%d failures, %d recovered from the backup, last error: %s", failure.Failures, failure.Recovered, failure.LastError),
+			})
+		}
+	}
+	return alerts
+}
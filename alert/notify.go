@@ -0,0 +1,94 @@
+package alert
+
+import (
+	"bytes"
+	"encoding/json"
+	"fmt"
+	"net/http"
+	"net/smtp"
+	"strings"
+	"time"
+)
+
+var slackHttpClient = &http.Client{
+	Timeout: 10 * time.Second,
+}
+
+type SmtpConfig struct {
+	Host     string   `yaml:"host"`
+	Port     int      `yaml:"port"`
+	Username string   `yaml:"username,omitempty"`
+	Password string   `yaml:"-"`
+	From     string   `yaml:"from"`
+	To       []string `yaml:"to"`
+}
+
+func (a *Alerter) channels(t EventType) []Channel {
+	if channels, ok := a.Routes[t]; ok {
+		return channels
+	}
+	var channels []Channel
+	if a.Smtp != nil {
+		channels = append(channels, ChannelEmail)
+	}
+	if a.SlackWebhookUrl != "" {
+		channels = append(channels, ChannelSlack)
+	}
+	return channels
+}
+
+func (a *Alerter) Notify(alert *Alert) {
+	for _, channel := range a.channels(alert.Type) {
+		var err error
+		switch channel {
+		case ChannelEmail:
+			err = a.sendEmail(alert)
+		case ChannelSlack:
+			err = a.sendSlack(alert)
+		default:
+			err = fmt.Errorf("unknown alert channel %s", channel)
+		}
+		if err != nil {
+			fmt.Printf("Error: %s", err)
+		}
+	}
+}
+
+func (a *Alerter) sendEmail(alert *Alert) error {
+	if a.Smtp == nil {
+		return fmt.Errorf("no smtp config to send the %s alert with", alert.Type)
+	}
+	var auth smtp.Auth
+	if a.Smtp.Username != "" {
+		auth = smtp.PlainAuth("", a.Smtp.Username, a.Smtp.Password, a.Smtp.Host)
+	}
+	msg := fmt.Sprintf("From: %s\r\nTo: %s\r\nSubject: [mindia] %s\r\n\r\n%s\r\n",
+		headerValue(a.Smtp.From), headerValue(strings.Join(a.Smtp.To, ", ")), headerValue(alert.Subject), alert.Message)
+	return smtp.SendMail(fmt.Sprintf("%s:%d", a.Smtp.Host, a.Smtp.Port), auth, a.Smtp.From, a.Smtp.To, []byte(msg))
+}
+
+// headerValue keeps task names and errors from injecting headers into the email.
+func headerValue(v string) string {
+	return strings.NewReplacer("\r", " ", "\n", " ").Replace(v)
+}
+
+func (a *Alerter) sendSlack(alert *Alert) error {
+	if a.SlackWebhookUrl == "" {
+		return fmt.Errorf("no slack webhook url to send the %s alert to", alert.Type)
+	}
+	body, err := json.Marshal(map[string]string{
+		"text": fmt.Sprintf("*%s*\n%s", alert.Subject, alert.Message),
+	})
+	if err != nil {
+		return err
+	}
+	res, err := slackHttpClient.Post(a.SlackWebhookUrl, "application/json", bytes.NewReader(body))
+	if err != nil {
+		return err
+	}
+	defer res.Body.Close()
+	if res.StatusCode >= 400 {
+		return fmt.Errorf("slack webhook answered %s", res.Status)
+	}
+	return nil
+}
@@ -3,23 +3,32 @@ package apiserver
 import (
 	"bytes"
 	"encoding/json"
+	"errors"
 	"fmt"
 	"io"
 	"mime/multipart"
+	"mindia/automation"
 	"mindia/folder"
+	"mindia/storage"
 	"net/http"
+	"strconv"
+	"sync"
 )
 
-const apiVersion = "v0"
-
 type ApiServerConfig struct {
-	Port int `yaml:"api_server"`
+	Port              int                          `yaml:"api_server"`
+	Maintenance       MaintenanceConfig            `yaml:"maintenance"`
+	Versions          map[string]*ApiVersionConfig `yaml:"versions,omitempty"`
+	SigningKey        string                       `yaml:"signing_key,omitempty"`
+	RedirectDownloads bool                         `yaml:"redirect_downloads,omitempty"`
 }
 
 type ApiServer struct {
 	folders          folder.FoldersMap
 	routes           []route
 	*ApiServerConfig `yaml:",inline"`
+	OnConfigChange   func() `yaml:"-"`
+	mu               sync.RWMutex
 }
 
 func NewApiServer(config *ApiServerConfig) *ApiServer {
@@ -30,16 +39,71 @@ func NewApiServer(config *ApiServerConfig) *ApiServer {
 }
 
 func (s *ApiServer) AddFolder(f *folder.Folder) {
+	s.mu.Lock()
 	s.folders[f.Dir] = f
+	s.mu.Unlock()
+}
+
+func (s *ApiServer) readFolder(dir string) (*folder.Folder, bool) {
+	s.mu.RLock()
+	defer s.mu.RUnlock()
+	f, ok := s.folders[dir]
+	return f, ok
+}
+
+func (s *ApiServer) readFolders() folder.FoldersMap {
+	s.mu.RLock()
+	defer s.mu.RUnlock()
+	folders := folder.FoldersMap{}
+	for dir, f := range s.folders {
+		folders[dir] = f
+	}
+	return folders
+}
+
+// Folders returns the folders served right now.
+func (s *ApiServer) Folders() []*folder.Folder {
+	var folders []*folder.Folder
+	for _, f := range s.readFolders() {
+		folders = append(folders, f)
+	}
+	return folders
 }
 
 func (s *ApiServer) Serve() {
 	s.routes = []route{
-		newRoute("GET", "/metadatas/folders", s.handleReadFolders),
-		newRoute("GET", "(/.*)/list", s.handleReadFolder),
-		newRoute("GET", "(/.*)/download/(.*)", s.handleDownload),
-		newRoute("POST", "(/.*)/upload", s.handleUpload),
-		newRoute("DELETE", "(/.*)/file/(.*)", s.handleDelete),
+		newRoute(groupAdmin, "GET", "/metadatas/maintenance", s.handleReadMaintenance),
+		newRoute(groupAdmin, "PUT", "/metadatas/maintenance", s.handleUpdateMaintenance),
+		newRoute(groupAdmin, "GET", "/metadatas/integrity", s.handleReadIntegrity),
+		newRoute(groupAdmin, "GET", "/metadatas/gc", s.handleReadGc),
+		newRoute(groupAdmin, "POST", "/metadatas/gc", s.handleRunGc),
+		newRoute(groupAdmin, "GET", "/metadatas/usage", s.handleReadUsage),
+		newRoute(groupAdmin, "GET", "/metadatas/optimizers", s.handleReadOptimizers),
+		newRoute(groupAdmin, "GET", "/metadatas/breakers", s.handleReadBreakers),
+		newRoute(groupAdmin, "GET", "/metadatas/tasks", s.handleReadTasks),
+		newRoute(groupAdmin, "GET", "/metadatas/tasks/dead-letter", s.handleReadDeadLetters),
+		newRoute(groupAdmin, "GET", "/metadatas/tasks/stats", s.handleReadTaskStats),
+		newRoute(groupAdmin, "GET", "/metadatas/tasks/([^/]+)", s.handleReadTask),
+		newRoute(groupAdmin, "POST", "/metadatas/tasks/([^/]+)/requeue", s.handleRequeueTask),
+		newRoute(groupMetadatas, "GET", "/metadatas/folders", s.handleReadFolders),
+		newRoute(groupMetadatas, "GET", "/metadatas/folders/tree", s.handleReadFolderTree),
+		newRoute(groupFiles, "GET", "(/.*)/list", s.handleReadFolder),
+		newRoute(groupFiles, "GET", "(/.*)/download/(.*)", s.handleDownload),
+		newRoute(groupFiles, "GET", "(/.*)/signed/(.*)", s.handleSignedDownload),
+		newRoute(groupFiles, "GET", "(/.*)/presign/(.*)", s.handlePresign),
+		newRoute(groupFiles, "GET", "(/.*)/exif/(.*)", s.handleExif),
+		newRoute(groupFiles, "GET", "(/.*)/dimensions/(.*)", s.handleDimensions),
+		newRoute(groupFiles, "GET", "(/.*)/similar/(.*)", s.handleSimilar),
+		newRoute(groupFiles, "GET", "(/.*)/palette/(.*)", s.handlePalette),
+		newRoute(groupFiles, "GET", "(/.*)/archive", s.handleArchive),
+		newRoute(groupFiles, "POST", "(/.*)/upload", s.handleUpload),
+		newRoute(groupFiles, "DELETE", "(/.*)/file/(.*)", s.handleDelete),
+		newRoute(groupFolders, "DELETE", "(/.*)/folder", s.handleDeleteFolder),
+		newRoute(groupFolders, "POST", "(/.*)/move", s.handleMoveFolder),
+		newRoute(groupExports, "POST", "(/.*)/exports", s.handleCreateExport),
+		newRoute(groupExports, "GET", "(/.*)/exports/(.*)", s.handleReadExport),
+		newRoute(groupImports, "POST", "(/.*)/imports", s.handleCreateImport),
+		newRoute(groupImports, "GET", "(/.*)/imports/(.*)", s.handleReadImport),
 	}
 
 	fmt.Printf("listening on port: %d\n", s.Port)
@@ -47,29 +111,158 @@ func (s *ApiServer) Serve() {
 }
 
 func (s *ApiServer) handleReadFolders(w http.ResponseWriter, r *http.Request) {
-	var folders []folder.Folder
-	for _, folder := range s.folders {
-		folders = append(folders, *folder)
+	var folders []*folder.Folder
+	for _, folder := range s.readFolders() {
+		folders = append(folders, folder)
 	}
-	writeJSON(w, folders)
+	writeJSON(w, r, folders)
 }
 
 func (s *ApiServer) handleReadFolder(w http.ResponseWriter, r *http.Request) {
-	files, _ := s.folders[getFolder(r)].ReadAll()
-	writeJSON(w, files)
+	f, ok := s.readFolder(getFolder(r))
+	if !ok {
+		http.NotFound(w, r)
+		return
+	}
+	files, _ := f.ReadAll()
+
+	if c := r.URL.Query().Get("color"); c != "" {
+		tolerance := 20.0
+		if v := r.URL.Query().Get("tolerance"); v != "" {
+			t, err := strconv.ParseFloat(v, 64)
+			if err != nil || t < 0 {
+				http.Error(w, "invalid tolerance", http.StatusBadRequest)
+				return
+			}
+			tolerance = t
+		}
+
+		var err error
+		files, err = f.FilterByColor(files, c, tolerance)
+		if err != nil {
+			http.Error(w, err.Error(), http.StatusBadRequest)
+			return
+		}
+	}
+
+	writeJSON(w, r, files)
 }
 
 func (s *ApiServer) handleDownload(w http.ResponseWriter, r *http.Request) {
-	bytes, err := s.folders[getFolder(r)].Download(getField(r, 1))
+	f, ok := s.readFolder(getFolder(r))
+	if !ok {
+		http.NotFound(w, r)
+		return
+	}
+	name := getField(r, 1)
+
+	ops, transform, err := parseTransforms(r)
 	if err != nil {
 		http.Error(w, err.Error(), http.StatusUnprocessableEntity)
 		return
 	}
+
+	if s.RedirectDownloads && transform == nil {
+		if url, err := f.PresignedUrl(name, defaultPresignExpiry); err == nil {
+			http.Redirect(w, r, url, http.StatusFound)
+			return
+		}
+	}
+
+	var bytes []byte
+	if transform != nil {
+		bytes, err = f.Transform(name, ops, transform)
+	} else {
+		bytes, err = f.Download(name)
+	}
+	if err != nil {
+		http.Error(w, err.Error(), http.StatusUnprocessableEntity)
+		return
+	}
+
 	writeBytes(w, bytes)
 }
 
+func (s *ApiServer) handleExif(w http.ResponseWriter, r *http.Request) {
+	f, ok := s.readFolder(getFolder(r))
+	if !ok {
+		http.NotFound(w, r)
+		return
+	}
+
+	x, err := f.ReadExif(getField(r, 1))
+	if err != nil {
+		http.Error(w, err.Error(), http.StatusUnprocessableEntity)
+		return
+	}
+	writeJSON(w, r, x)
+}
+
+func (s *ApiServer) handleDimensions(w http.ResponseWriter, r *http.Request) {
+	f, ok := s.readFolder(getFolder(r))
+	if !ok {
+		http.NotFound(w, r)
+		return
+	}
+
+	d, err := f.ReadDimensions(getField(r, 1))
+	if err != nil {
+		http.Error(w, err.Error(), http.StatusUnprocessableEntity)
+		return
+	}
+	writeJSON(w, r, d)
+}
+
+func (s *ApiServer) handleSimilar(w http.ResponseWriter, r *http.Request) {
+	threshold := 8
+	if v := r.URL.Query().Get("threshold"); v != "" {
+		t, err := strconv.Atoi(v)
+		if err != nil || t < 0 || t > 64 {
+			http.Error(w, "invalid threshold, expected 0 to 64", http.StatusBadRequest)
+			return
+		}
+		threshold = t
+	}
+
+	f, ok := s.readFolder(getFolder(r))
+	if !ok {
+		http.NotFound(w, r)
+		return
+	}
+
+	similar, err := f.FindSimilar(getField(r, 1), threshold)
+	if err != nil {
+		http.Error(w, err.Error(), http.StatusUnprocessableEntity)
+		return
+	}
+	writeJSON(w, r, similar)
+}
+
+func (s *ApiServer) handlePalette(w http.ResponseWriter, r *http.Request) {
+	f, ok := s.readFolder(getFolder(r))
+	if !ok {
+		http.NotFound(w, r)
+		return
+	}
+
+	p, err := f.ReadPalette(getField(r, 1))
+	if err != nil {
+		http.Error(w, err.Error(), http.StatusUnprocessableEntity)
+		return
+	}
+	writeJSON(w, r, p)
+}
+
 func (s *ApiServer) handleUpload(w http.ResponseWriter, r *http.Request) {
-	folder := getFolder(r)
+	f, ok := s.readFolder(getFolder(r))
+	if !ok {
+		http.NotFound(w, r)
+		return
+	}
+
+	if s.rejectSaturated(w, f) {
+		return
+	}
 
 	err := r.ParseMultipartForm(10 << 20) // Maximum upload of 10 MB files
 	if err != nil {
@@ -97,17 +290,31 @@ func (s *ApiServer) handleUpload(w http.ResponseWriter, r *http.Request) {
 		return
 	}
 
-	err = s.folders[folder].Upload(handler.Filename, buf.Bytes())
+	out, err := f.Upload(handler.Filename, buf.Bytes())
+	if errors.Is(err, storage.ErrQuotaExceeded) {
+		http.Error(w, err.Error(), http.StatusInsufficientStorage)
+		return
+	}
+	if errors.Is(err, automation.ErrInvalidSvg) {
+		http.Error(w, err.Error(), http.StatusUnprocessableEntity)
+		return
+	}
 	if err != nil {
 		http.Error(w, err.Error(), http.StatusInternalServerError)
 		return
 	}
 
-	http.Error(w, "File uploaded", http.StatusOK)
+	writeJSON(w, r, out)
 }
 
 func (s *ApiServer) handleDelete(w http.ResponseWriter, r *http.Request) {
-	err := s.folders[getFolder(r)].DeleteOne(getField(r, 1))
+	f, ok := s.readFolder(getFolder(r))
+	if !ok {
+		http.NotFound(w, r)
+		return
+	}
+
+	err := f.DeleteOne(getField(r, 1))
 	if err != nil {
 		http.Error(w, err.Error(), http.StatusUnprocessableEntity)
 		return
@@ -120,14 +327,14 @@ func (s *ApiServer) handleDelete(w http.ResponseWriter, r *http.Request) {
 	w.WriteHeader(http.StatusOK)
 }
 
-func writeJSON(w http.ResponseWriter, obj interface{}) {
-	jsonContent, err := json.MarshalIndent(obj, "", "	")
+func writeJSON(w http.ResponseWriter, r *http.Request, obj interface{}) {
+	jsonContent, err := json.MarshalIndent(shapeResponse(r, obj), "", "	")
 	if err != nil {
 		http.Error(w, fmt.Sprintf("error building the response, %v", err), http.StatusInternalServerError)
 		return
 	}
-	w.WriteHeader(http.StatusOK)
 	w.Header().Set("Content-Type", "application/json")
+	w.WriteHeader(http.StatusOK)
 	w.Write(jsonContent)
 }
 
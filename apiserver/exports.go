@@ -0,0 +1,86 @@
+package apiserver
+
+import (
+	"encoding/json"
+	"fmt"
+	"io"
+	"mindia/folder"
+	"net/http"
+	"time"
+)
+
+type exportResponse struct {
+	*folder.Export
+	ArchiveUrls  []string  `json:"archive_urls"`
+	ManifestUrl  string    `json:"manifest_url,omitempty"`
+	UrlsExpireAt time.Time `json:"urls_expire_at"`
+}
+
+func downloadUrl(r *http.Request, dir, name string) string {
+	return fmt.Sprintf("/%s%s/download/%s", getVersion(r), dir, name)
+}
+
+// exportUrl is signed so the archives can be handed out, a plain download url
+// is only returned when the storage can't presign and no signing key is set.
+func (s *ApiServer) exportUrl(r *http.Request, f *folder.Folder, name string, expiresAt time.Time) string {
+	url, err := s.presign(r, f, name, expiresAt)
+	if err != nil {
+		return downloadUrl(r, f.Dir, name)
+	}
+	return url
+}
+
+func (s *ApiServer) newExportResponse(r *http.Request, f *folder.Folder, e *folder.Export) *exportResponse {
+	res := &exportResponse{
+		Export:       e,
+		ArchiveUrls:  []string{},
+		UrlsExpireAt: time.Now().Add(defaultPresignExpiry),
+	}
+	for _, archive := range e.Archives {
+		res.ArchiveUrls = append(res.ArchiveUrls, s.exportUrl(r, f, archive, res.UrlsExpireAt))
+	}
+	if e.Manifest != "" {
+		res.ManifestUrl = s.exportUrl(r, f, e.Manifest, res.UrlsExpireAt)
+	}
+	return res
+}
+
+func (s *ApiServer) handleCreateExport(w http.ResponseWriter, r *http.Request) {
+	f, ok := s.readFolder(getFolder(r))
+	if !ok {
+		http.NotFound(w, r)
+		return
+	}
+
+	if s.rejectSaturated(w, f) {
+		return
+	}
+
+	in := &folder.ExportInput{}
+	err := json.NewDecoder(r.Body).Decode(in)
+	if err != nil && err != io.EOF {
+		http.Error(w, err.Error(), http.StatusUnprocessableEntity)
+		return
+	}
+	if !in.Priority.IsValid() {
+		http.Error(w, "priority must be low, normal or high", http.StatusUnprocessableEntity)
+		return
+	}
+
+	writeJSON(w, r, s.newExportResponse(r, f, f.Export(in)))
+}
+
+func (s *ApiServer) handleReadExport(w http.ResponseWriter, r *http.Request) {
+	f, ok := s.readFolder(getFolder(r))
+	if !ok {
+		http.NotFound(w, r)
+		return
+	}
+
+	e := f.ReadExport(getField(r, 1))
+	if e == nil {
+		http.NotFound(w, r)
+		return
+	}
+	writeJSON(w, r, s.newExportResponse(r, f, e))
+}
@@ -0,0 +1,146 @@
+package apiserver
+
+import (
+	"encoding/json"
+	"fmt"
+	"mindia/folder"
+	"mindia/types"
+	"net/http"
+	"path"
+	"strconv"
+	"strings"
+)
+
+type moveFolderInput struct {
+	Dir string `json:"dir"`
+}
+
+func (s *ApiServer) subfolders(dir string) []*folder.Folder {
+	var folders []*folder.Folder
+	for _, f := range s.readFolders() {
+		if f.Dir == dir || strings.HasPrefix(f.Dir, dir+"/") {
+			folders = append(folders, f)
+		}
+	}
+	return folders
+}
+
+func (s *ApiServer) handleReadFolderTree(w http.ResponseWriter, r *http.Request) {
+	writeJSON(w, r, folder.BuildTree(s.readFolders()))
+}
+
+func (s *ApiServer) handleDeleteFolder(w http.ResponseWriter, r *http.Request) {
+	dir := getFolder(r)
+	if _, ok := s.readFolder(dir); !ok {
+		http.NotFound(w, r)
+		return
+	}
+
+	folders := s.subfolders(dir)
+	if len(folders) > 1 && r.URL.Query().Get("recursive") != "true" {
+		http.Error(w, "folder has subfolders, use recursive=true to delete them", http.StatusConflict)
+		return
+	}
+
+	for _, f := range folders {
+		err := f.DeleteAll()
+		if err != nil {
+			http.Error(w, err.Error(), http.StatusInternalServerError)
+			return
+		}
+		f.Close()
+
+		s.mu.Lock()
+		delete(s.folders, f.Dir)
+		s.mu.Unlock()
+	}
+
+	fmt.Fprintf(w, "Successfully Deleted Folder\n")
+}
+
+func (s *ApiServer) handleMoveFolder(w http.ResponseWriter, r *http.Request) {
+	dir := getFolder(r)
+	if _, ok := s.readFolder(dir); !ok {
+		http.NotFound(w, r)
+		return
+	}
+
+	var in moveFolderInput
+	err := json.NewDecoder(r.Body).Decode(&in)
+	if err != nil {
+		http.Error(w, err.Error(), http.StatusUnprocessableEntity)
+		return
+	}
+	if !strings.HasPrefix(in.Dir, "/") || in.Dir == dir || strings.HasPrefix(in.Dir, dir+"/") {
+		http.Error(w, fmt.Sprintf("invalid destination dir %s", in.Dir), http.StatusUnprocessableEntity)
+		return
+	}
+
+	folders := s.subfolders(dir)
+	for _, f := range folders {
+		if _, ok := s.readFolder(in.Dir + strings.TrimPrefix(f.Dir, dir)); ok {
+			http.Error(w, fmt.Sprintf("folder %s already exists", in.Dir+strings.TrimPrefix(f.Dir, dir)), http.StatusConflict)
+			return
+		}
+	}
+
+	for _, f := range folders {
+		src := f.Dir
+		err = f.Move(in.Dir + strings.TrimPrefix(src, dir))
+		if err != nil {
+			http.Error(w, err.Error(), http.StatusInternalServerError)
+			return
+		}
+
+		s.mu.Lock()
+		delete(s.folders, src)
+		s.folders[f.Dir] = f
+		s.mu.Unlock()
+	}
+
+	writeJSON(w, r, folder.BuildTree(s.readFolders()))
+}
+
+func (s *ApiServer) handleArchive(w http.ResponseWriter, r *http.Request) {
+	f, ok := s.readFolder(getFolder(r))
+	if !ok {
+		http.NotFound(w, r)
+		return
+	}
+
+	in := &folder.ArchiveInput{
+		Format: r.URL.Query().Get("format"),
+	}
+	if in.Format == "" {
+		in.Format = folder.ArchiveZip
+	}
+	if in.Format != folder.ArchiveZip && in.Format != folder.ArchiveTarGz {
+		http.Error(w, fmt.Sprintf("unsupported archive format %s", in.Format), http.StatusUnprocessableEntity)
+		return
+	}
+
+	if r.URL.Query().Has("width") || r.URL.Query().Has("height") {
+		width, _ := strconv.Atoi(r.URL.Query().Get("width"))
+		height, _ := strconv.Atoi(r.URL.Query().Get("height"))
+		if width <= 0 || height <= 0 {
+			http.Error(w, "width and height must be positive integers", http.StatusUnprocessableEntity)
+			return
+		}
+		in.Size = &types.Size{
+			Width:  int32(width),
+			Height: int32(height),
+		}
+	}
+
+	contentType := "application/zip"
+	if in.Format == folder.ArchiveTarGz {
+		contentType = "application/gzip"
+	}
+	w.Header().Set("Content-Type", contentType)
+	w.Header().Set("Content-Disposition", fmt.Sprintf("attachment; filename=%q", path.Base(f.Dir)+"."+in.Format))
+
+	err := f.WriteArchive(w, in)
+	if err != nil {
+		fmt.Printf("Error: %s", err)
+	}
+}
@@ -0,0 +1,54 @@
+package apiserver
+
+import (
+	"encoding/json"
+	"mindia/folder"
+	"net/http"
+)
+
+func (s *ApiServer) handleCreateImport(w http.ResponseWriter, r *http.Request) {
+	f, ok := s.readFolder(getFolder(r))
+	if !ok {
+		http.NotFound(w, r)
+		return
+	}
+
+	if s.rejectSaturated(w, f) {
+		return
+	}
+
+	in := &folder.ImportInput{}
+	err := json.NewDecoder(r.Body).Decode(in)
+	if err != nil {
+		http.Error(w, err.Error(), http.StatusUnprocessableEntity)
+		return
+	}
+	if len(in.Urls) == 0 && in.S3 == nil {
+		http.Error(w, "either urls or s3 must be provided", http.StatusUnprocessableEntity)
+		return
+	}
+	if !in.Priority.IsValid() {
+		http.Error(w, "priority must be low, normal or high", http.StatusUnprocessableEntity)
+		return
+	}
+
+	writeJSON(w, r, f.Import(in, func(dir string) *folder.Folder {
+		f, _ := s.readFolder(dir)
+		return f
+	}))
+}
+
+func (s *ApiServer) handleReadImport(w http.ResponseWriter, r *http.Request) {
+	f, ok := s.readFolder(getFolder(r))
+	if !ok {
+		http.NotFound(w, r)
+		return
+	}
+
+	i := f.ReadImport(getField(r, 1))
+	if i == nil {
+		http.NotFound(w, r)
+		return
+	}
+	writeJSON(w, r, i)
+}
@@ -0,0 +1,47 @@
+package apiserver
+
+import (
+	"mindia/breaker"
+	"mindia/folder"
+	"net/http"
+	"sort"
+)
+
+func (s *ApiServer) handleReadIntegrity(w http.ResponseWriter, r *http.Request) {
+	report := []*folder.IntegrityFailure{}
+	for _, f := range s.readFolders() {
+		report = append(report, f.IntegrityReport()...)
+	}
+	sort.SliceStable(report, func(i, j int) bool {
+		return report[i].Failures > report[j].Failures
+	})
+	writeJSON(w, r, report)
+}
+
+func (s *ApiServer) handleReadGc(w http.ResponseWriter, r *http.Request) {
+	reports := []*folder.GcReport{}
+	for _, f := range s.readFolders() {
+		if report := f.GcReport(); report != nil {
+			reports = append(reports, report)
+		}
+	}
+	sort.SliceStable(reports, func(i, j int) bool {
+		return reports[i].Dir < reports[j].Dir
+	})
+	writeJSON(w, r, reports)
+}
+
+func (s *ApiServer) handleRunGc(w http.ResponseWriter, r *http.Request) {
+	reports := []*folder.GcReport{}
+	for _, f := range s.readFolders() {
+		reports = append(reports, f.CollectGarbage())
+	}
+	sort.SliceStable(reports, func(i, j int) bool {
+		return reports[i].Dir < reports[j].Dir
+	})
+	writeJSON(w, r, reports)
+}
+
+func (s *ApiServer) handleReadBreakers(w http.ResponseWriter, r *http.Request) {
+	writeJSON(w, r, breaker.Statuses())
+}
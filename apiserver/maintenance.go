@@ -0,0 +1,135 @@
+package apiserver
+
+import (
+	"encoding/json"
+	"errors"
+	"fmt"
+	"net/http"
+	"strconv"
+)
+
+const (
+	groupAdmin     = "admin"
+	groupMetadatas = "metadatas"
+	groupFiles     = "files"
+	groupFolders   = "folders"
+	groupExports   = "exports"
+	groupImports   = "imports"
+)
+
+const defaultRetryAfter = 120
+
+var maintenanceGroups = []string{groupMetadatas, groupFiles, groupFolders, groupExports, groupImports}
+
+type MaintenanceConfig struct {
+	ReadOnly   bool     `yaml:"read_only" json:"read_only"`
+	Groups     []string `yaml:"groups" json:"groups"`
+	RetryAfter int      `yaml:"retry_after" json:"retry_after"`
+}
+
+func (m *MaintenanceConfig) Validate() error {
+	for _, group := range m.Groups {
+		if group == groupAdmin {
+			return errors.New("the admin group cannot be put under maintenance")
+		}
+		if !isMaintenanceGroup(group) {
+			return fmt.Errorf("unknown group %s, expected one of %v", group, maintenanceGroups)
+		}
+	}
+	return nil
+}
+
+func isMaintenanceGroup(group string) bool {
+	for _, g := range maintenanceGroups {
+		if g == group {
+			return true
+		}
+	}
+	return false
+}
+
+// SetMaintenance restores a maintenance state, typically the one persisted by a previous run.
+func (s *ApiServer) SetMaintenance(m MaintenanceConfig) error {
+	err := m.Validate()
+	if err != nil {
+		return err
+	}
+	s.mu.Lock()
+	s.Maintenance = m
+	s.mu.Unlock()
+	return nil
+}
+
+func isReadMethod(method string) bool {
+	return method == http.MethodGet || method == http.MethodHead || method == http.MethodOptions
+}
+
+// MarshalYAML snapshots the config under the lock, since the maintenance state
+// is updated while the config is persisted.
+func (s *ApiServer) MarshalYAML() (interface{}, error) {
+	s.mu.RLock()
+	defer s.mu.RUnlock()
+
+	config := *s.ApiServerConfig
+	return &config, nil
+}
+
+func (s *ApiServer) rejectMaintenance(w http.ResponseWriter, route route) bool {
+	if route.group == groupAdmin {
+		return false
+	}
+
+	s.mu.RLock()
+	m := s.Maintenance
+	s.mu.RUnlock()
+
+	reject := m.ReadOnly && !isReadMethod(route.method)
+	for _, group := range m.Groups {
+		if group == route.group {
+			reject = true
+		}
+	}
+	if !reject {
+		return false
+	}
+
+	retryAfter := m.RetryAfter
+	if retryAfter <= 0 {
+		retryAfter = defaultRetryAfter
+	}
+	w.Header().Set("Retry-After", strconv.Itoa(retryAfter))
+	if m.ReadOnly && !isReadMethod(route.method) {
+		http.Error(w, "503 api server is in read-only mode", http.StatusServiceUnavailable)
+	} else {
+		http.Error(w, "503 "+route.group+" are under maintenance", http.StatusServiceUnavailable)
+	}
+	return true
+}
+
+func (s *ApiServer) handleReadMaintenance(w http.ResponseWriter, r *http.Request) {
+	s.mu.RLock()
+	m := s.Maintenance
+	s.mu.RUnlock()
+
+	writeJSON(w, r, m)
+}
+
+func (s *ApiServer) handleUpdateMaintenance(w http.ResponseWriter, r *http.Request) {
+	var m MaintenanceConfig
+	err := json.NewDecoder(r.Body).Decode(&m)
+	if err != nil {
+		http.Error(w, err.Error(), http.StatusUnprocessableEntity)
+		return
+	}
+	err = s.SetMaintenance(m)
+	if err != nil {
+		http.Error(w, err.Error(), http.StatusUnprocessableEntity)
+		return
+	}
+
+	if s.OnConfigChange != nil {
+		s.OnConfigChange()
+	}
+
+	writeJSON(w, r, m)
+}
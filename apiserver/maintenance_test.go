@@ -0,0 +1,42 @@
+package apiserver
+
+import (
+	"strings"
+	"sync"
+	"testing"
+
+	"gopkg.in/yaml.v3"
+)
+
+// run with -race, persisting the config used to read the maintenance state unlocked
+func TestMarshalDuringSetMaintenance(t *testing.T) {
+	s := NewApiServer(&ApiServerConfig{Port: 3500})
+
+	var wg sync.WaitGroup
+	wg.Add(1)
+	go func() {
+		defer wg.Done()
+		for i := 0; i < 100; i++ {
+			err := s.SetMaintenance(MaintenanceConfig{ReadOnly: i%2 == 0, Groups: []string{groupFiles}})
+			if err != nil {
+				t.Error(err)
+				return
+			}
+		}
+	}()
+	for i := 0; i < 100; i++ {
+		_, err := yaml.Marshal(s)
+		if err != nil {
+			t.Fatal(err)
+		}
+	}
+	wg.Wait()
+
+	b, err := yaml.Marshal(s)
+	if err != nil {
+		t.Fatal(err)
+	}
+	if !strings.Contains(string(b), "api_server: 3500") || !strings.Contains(string(b), "- files") {
+		t.Errorf("got config %s", b)
+	}
+}
@@ -0,0 +1,126 @@
+package apiserver
+
+import (
+	"crypto/hmac"
+	"crypto/sha256"
+	"encoding/hex"
+	"errors"
+	"fmt"
+	"mindia/folder"
+	"mindia/storage"
+	"net/http"
+	"strconv"
+	"time"
+)
+
+const (
+	defaultPresignExpiry = 15 * time.Minute
+	maxPresignExpiry     = 7 * 24 * time.Hour
+)
+
+type presignResponse struct {
+	Url       string    `json:"url"`
+	ExpiresAt time.Time `json:"expires_at"`
+}
+
+func (s *ApiServer) sign(path string, expires int64) string {
+	mac := hmac.New(sha256.New, []byte(s.SigningKey))
+	fmt.Fprintf(mac, "%s\n%d", path, expires)
+	return hex.EncodeToString(mac.Sum(nil))
+}
+
+func (s *ApiServer) verifySignature(r *http.Request) bool {
+	if s.SigningKey == "" {
+		return false
+	}
+	expires, err := strconv.ParseInt(r.URL.Query().Get("expires"), 10, 64)
+	if err != nil || time.Now().Unix() > expires {
+		return false
+	}
+	signature := r.URL.Query().Get("signature")
+	return hmac.Equal([]byte(signature), []byte(s.sign(r.URL.Path, expires)))
+}
+
+func signedUrl(r *http.Request, dir, name string) string {
+	return fmt.Sprintf("/%s%s/signed/%s", getVersion(r), dir, name)
+}
+
+func (s *ApiServer) presign(r *http.Request, f *folder.Folder, name string, expiresAt time.Time) (string, error) {
+	url, err := f.PresignedUrl(name, time.Until(expiresAt))
+	if errors.Is(err, storage.ErrPresignNotSupported) && s.SigningKey != "" {
+		path := signedUrl(r, f.Dir, name)
+		expires := expiresAt.Unix()
+		return fmt.Sprintf("%s?expires=%d&signature=%s", path, expires, s.sign(path, expires)), nil
+	}
+	return url, err
+}
+
+func (s *ApiServer) handlePresign(w http.ResponseWriter, r *http.Request) {
+	f, ok := s.readFolder(getFolder(r))
+	if !ok {
+		http.NotFound(w, r)
+		return
+	}
+
+	expiry := defaultPresignExpiry
+	if v := r.URL.Query().Get("expiry"); v != "" {
+		seconds, err := strconv.Atoi(v)
+		if err != nil || seconds <= 0 || time.Duration(seconds)*time.Second > maxPresignExpiry {
+			http.Error(w, "invalid expiry, expected seconds up to 7 days", http.StatusBadRequest)
+			return
+		}
+		expiry = time.Duration(seconds) * time.Second
+	}
+
+	exists, err := f.DoesExist(getField(r, 1))
+	if err != nil {
+		http.Error(w, err.Error(), http.StatusUnprocessableEntity)
+		return
+	}
+	if !exists {
+		http.NotFound(w, r)
+		return
+	}
+
+	expiresAt := time.Now().Add(expiry)
+	url, err := s.presign(r, f, getField(r, 1), expiresAt)
+	if errors.Is(err, storage.ErrPresignNotSupported) {
+		http.Error(w, err.Error(), http.StatusNotImplemented)
+		return
+	}
+	if err != nil {
+		http.Error(w, err.Error(), http.StatusUnprocessableEntity)
+		return
+	}
+
+	writeJSON(w, r, &presignResponse{
+		Url:       url,
+		ExpiresAt: expiresAt,
+	})
+}
+
+// handleSignedDownload serves the urls signed by presign, the query is not
+// covered by the signature so transforms are not applied.
+func (s *ApiServer) handleSignedDownload(w http.ResponseWriter, r *http.Request) {
+	if !s.verifySignature(r) {
+		http.Error(w, "invalid or expired signature", http.StatusForbidden)
+		return
+	}
+
+	f, ok := s.readFolder(getFolder(r))
+	if !ok {
+		http.NotFound(w, r)
+		return
+	}
+
+	bytes, err := f.Download(getField(r, 1))
+	if err != nil {
+		http.Error(w, err.Error(), http.StatusUnprocessableEntity)
+		return
+	}
+	if bytes == nil {
+		http.NotFound(w, r)
+		return
+	}
+	writeBytes(w, bytes)
+}
@@ -8,25 +8,40 @@ import (
 )
 
 type route struct {
+	group   string
 	method  string
 	regex   *regexp.Regexp
 	handler http.HandlerFunc
 }
 
-func newRoute(method, pattern string, handler http.HandlerFunc) route {
-	return route{method, regexp.MustCompile("^" + "/" + apiVersion + pattern + "$"), handler}
+var versionRegex = regexp.MustCompile("^/(v[0-9]+)(/.*)$")
+
+func newRoute(group, method, pattern string, handler http.HandlerFunc) route {
+	return route{group, method, regexp.MustCompile("^" + pattern + "$"), handler}
 }
 
 func (s *ApiServer) router(w http.ResponseWriter, r *http.Request) {
+	versionMatches := versionRegex.FindStringSubmatch(r.URL.Path)
+	if len(versionMatches) == 0 || !isApiVersion(versionMatches[1]) {
+		http.NotFound(w, r)
+		return
+	}
+	version, path := versionMatches[1], versionMatches[2]
+
 	var allow []string
 	for _, route := range s.routes {
-		matches := route.regex.FindStringSubmatch(r.URL.Path)
+		matches := route.regex.FindStringSubmatch(path)
 		if len(matches) > 0 {
 			if r.Method != route.method {
 				allow = append(allow, route.method)
 				continue
 			}
+			s.writeVersionHeaders(w, version, path)
+			if s.rejectMaintenance(w, route) {
+				return
+			}
 			ctx := context.WithValue(r.Context(), ctxKey{}, matches[1:])
+			ctx = context.WithValue(ctx, versionCtxKey{}, version)
 			route.handler(w, r.WithContext(ctx))
 			return
 		}
@@ -0,0 +1,93 @@
+package apiserver
+
+import (
+	"mindia/folder"
+	"mindia/scheduler"
+	"net/http"
+	"sort"
+	"strconv"
+)
+
+func (s *ApiServer) schedulers() []*scheduler.Scheduler {
+	seen := map[*scheduler.Scheduler]bool{}
+	var schedulers []*scheduler.Scheduler
+	for _, f := range s.readFolders() {
+		if f.Scheduler != nil && !seen[f.Scheduler] {
+			seen[f.Scheduler] = true
+			schedulers = append(schedulers, f.Scheduler)
+		}
+	}
+	return schedulers
+}
+
+func (s *ApiServer) handleReadTasks(w http.ResponseWriter, r *http.Request) {
+	tasks := []*scheduler.Task{}
+	for _, sc := range s.schedulers() {
+		tasks = append(tasks, sc.ReadTasks()...)
+	}
+	sort.SliceStable(tasks, func(i, j int) bool {
+		return tasks[i].CreatedAt.Before(tasks[j].CreatedAt)
+	})
+	writeJSON(w, r, tasks)
+}
+
+func (s *ApiServer) handleReadTask(w http.ResponseWriter, r *http.Request) {
+	for _, sc := range s.schedulers() {
+		if t := sc.ReadTask(getField(r, 0)); t != nil {
+			writeJSON(w, r, t)
+			return
+		}
+	}
+	http.NotFound(w, r)
+}
+
+func (s *ApiServer) handleReadDeadLetters(w http.ResponseWriter, r *http.Request) {
+	tasks := []*scheduler.Task{}
+	for _, sc := range s.schedulers() {
+		tasks = append(tasks, sc.ReadDeadLetters()...)
+	}
+	sort.SliceStable(tasks, func(i, j int) bool {
+		return tasks[i].FinishedAt.After(tasks[j].FinishedAt)
+	})
+	writeJSON(w, r, tasks)
+}
+
+func (s *ApiServer) handleRequeueTask(w http.ResponseWriter, r *http.Request) {
+	for _, sc := range s.schedulers() {
+		if sc.ReadTask(getField(r, 0)) == nil {
+			continue
+		}
+		t, err := sc.Requeue(getField(r, 0))
+		if err != nil {
+			http.Error(w, err.Error(), http.StatusConflict)
+			return
+		}
+		writeJSON(w, r, t)
+		return
+	}
+	http.NotFound(w, r)
+}
+
+func (s *ApiServer) handleReadTaskStats(w http.ResponseWriter, r *http.Request) {
+	stats := []*scheduler.SchedulerStats{}
+	for _, sc := range s.schedulers() {
+		stats = append(stats, sc.Stats())
+	}
+	writeJSON(w, r, stats)
+}
+
+func (s *ApiServer) rejectSaturated(w http.ResponseWriter, f *folder.Folder) bool {
+	if f == nil || f.Scheduler == nil || !f.Scheduler.Saturated() {
+		return false
+	}
+
+	s.mu.RLock()
+	retryAfter := s.Maintenance.RetryAfter
+	s.mu.RUnlock()
+	if retryAfter <= 0 {
+		retryAfter = defaultRetryAfter
+	}
+	w.Header().Set("Retry-After", strconv.Itoa(retryAfter))
+	http.Error(w, "503 task queue is saturated", http.StatusServiceUnavailable)
+	return true
+}
@@ -0,0 +1,146 @@
+package apiserver
+
+import (
+	"fmt"
+	"math"
+	"mindia/automation"
+	"mindia/folder"
+	"mindia/types"
+	"net/http"
+	"strconv"
+	"strings"
+)
+
+func parseRect(s string) (types.Rect, error) {
+	parts := strings.Split(s, ",")
+	if len(parts) != 4 {
+		return types.Rect{}, fmt.Errorf("invalid region %s, expected x,y,w,h", s)
+	}
+
+	var values [4]int32
+	for i, part := range parts {
+		v, err := strconv.ParseInt(strings.TrimSpace(part), 10, 32)
+		if err != nil || v < 0 {
+			return types.Rect{}, fmt.Errorf("invalid region %s, expected x,y,w,h", s)
+		}
+		values[i] = int32(v)
+	}
+	if int64(values[0])+int64(values[2]) > math.MaxInt32 || int64(values[1])+int64(values[3]) > math.MaxInt32 {
+		return types.Rect{}, fmt.Errorf("invalid region %s, out of bounds", s)
+	}
+
+	return types.Rect{
+		X:      values[0],
+		Y:      values[1],
+		Width:  values[2],
+		Height: values[3],
+	}, nil
+}
+
+func parseRedactions(r *http.Request) ([]automation.Redaction, error) {
+	var redactions []automation.Redaction
+
+	params := []struct {
+		name string
+		mode automation.RedactionMode
+	}{
+		{"blur_region", automation.RedactionBlur},
+		{"pixelate_region", automation.RedactionPixelate},
+	}
+	for _, param := range params {
+		for _, value := range r.URL.Query()[param.name] {
+			region, err := parseRect(value)
+			if err != nil {
+				return nil, err
+			}
+			redactions = append(redactions, automation.Redaction{
+				Mode:   param.mode,
+				Region: region,
+			})
+		}
+	}
+
+	return redactions, nil
+}
+
+func redactionsKey(redactions []automation.Redaction) string {
+	var parts []string
+	for _, redaction := range redactions {
+		region := redaction.Region
+		parts = append(parts, fmt.Sprintf("%s:%d,%d,%d,%d", redaction.Mode, region.X, region.Y, region.Width, region.Height))
+	}
+	return strings.Join(parts, ";")
+}
+
+func parseCaption(r *http.Request) (*automation.Caption, error) {
+	query := r.URL.Query()
+	text := query.Get("text")
+	if text == "" {
+		return nil, nil
+	}
+	if len(text) > automation.MaxCaptionLength {
+		return nil, fmt.Errorf("text is longer than %d characters", automation.MaxCaptionLength)
+	}
+
+	caption := &automation.Caption{
+		Text:       text,
+		Position:   automation.TextPosition(query.Get("text_position")),
+		Color:      query.Get("text_color"),
+		Background: query.Get("text_background"),
+		Shadow:     query.Get("text_shadow") == "true",
+	}
+	if size := query.Get("text_size"); size != "" {
+		v, err := strconv.Atoi(size)
+		if err != nil || v <= 0 || v > 512 {
+			return nil, fmt.Errorf("invalid text_size %s", size)
+		}
+		caption.Size = v
+	}
+	return caption, nil
+}
+
+func captionKey(caption *automation.Caption) string {
+	return fmt.Sprintf("text:%q,%s,%d,%s,%s,%t", caption.Text, caption.Position, caption.Size, caption.Color, caption.Background, caption.Shadow)
+}
+
+func parseTransforms(r *http.Request) (string, folder.TransformFunc, error) {
+	redactions, err := parseRedactions(r)
+	if err != nil {
+		return "", nil, err
+	}
+	caption, err := parseCaption(r)
+	if err != nil {
+		return "", nil, err
+	}
+
+	var (
+		ops        []string
+		transforms []folder.TransformFunc
+	)
+	if len(redactions) > 0 {
+		ops = append(ops, redactionsKey(redactions))
+		transforms = append(transforms, func(b []byte) ([]byte, error) {
+			return automation.Redact(b, redactions)
+		})
+	}
+	if caption != nil {
+		ops = append(ops, captionKey(caption))
+		transforms = append(transforms, func(b []byte) ([]byte, error) {
+			return automation.DrawCaption(b, caption)
+		})
+	}
+	if len(transforms) == 0 {
+		return "", nil, nil
+	}
+
+	return strings.Join(ops, "|"), func(b []byte) ([]byte, error) {
+		var err error
+		for _, transform := range transforms {
+			b, err = transform(b)
+			if err != nil {
+				return nil, err
+			}
+		}
+		return b, nil
+	}, nil
+}
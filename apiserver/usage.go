@@ -0,0 +1,52 @@
+package apiserver
+
+import (
+	"mindia/automation"
+	"mindia/storage"
+	"net/http"
+)
+
+type optimizerUsage struct {
+	BytesSaved int64 `json:"bytes_saved"`
+}
+
+type folderUsage struct {
+	Dir string `json:"dir"`
+	*storage.FilesystemUsage
+}
+
+func (s *ApiServer) handleReadUsage(w http.ResponseWriter, r *http.Request) {
+	usages := []*folderUsage{}
+	for _, f := range s.readFolders() {
+		fs, ok := f.Storage.(*storage.FilesystemStorage)
+		if !ok {
+			continue
+		}
+		usage, err := fs.Usage()
+		if err != nil {
+			http.Error(w, err.Error(), http.StatusInternalServerError)
+			return
+		}
+		usages = append(usages, &folderUsage{
+			Dir:             f.Dir,
+			FilesystemUsage: usage,
+		})
+	}
+	writeJSON(w, r, usages)
+}
+
+func (s *ApiServer) handleReadOptimizers(w http.ResponseWriter, r *http.Request) {
+	seen := map[*automation.Optimizer]bool{}
+	usage := &optimizerUsage{}
+	for _, f := range s.readFolders() {
+		for _, a := range f.Automations {
+			for _, o := range a.Automation.Optimizers() {
+				if !seen[o] {
+					seen[o] = true
+					usage.BytesSaved += o.BytesSaved()
+				}
+			}
+		}
+	}
+	writeJSON(w, r, usage)
+}
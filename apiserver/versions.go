@@ -0,0 +1,67 @@
+package apiserver
+
+import (
+	"fmt"
+	"net/http"
+	"time"
+)
+
+const (
+	apiV0 = "v0"
+	apiV1 = "v1"
+)
+
+const latestApiVersion = apiV1
+
+type ApiVersionConfig struct {
+	Deprecation time.Time `yaml:"deprecation,omitempty"`
+	Sunset      time.Time `yaml:"sunset,omitempty"`
+}
+
+type versionCtxKey struct{}
+
+type envelope struct {
+	Data interface{} `json:"data"`
+}
+
+var responseShapers = map[string]func(obj interface{}) interface{}{
+	apiV0: func(obj interface{}) interface{} {
+		return obj
+	},
+	apiV1: func(obj interface{}) interface{} {
+		return &envelope{Data: obj}
+	},
+}
+
+func isApiVersion(version string) bool {
+	_, ok := responseShapers[version]
+	return ok
+}
+
+func getVersion(r *http.Request) string {
+	version, ok := r.Context().Value(versionCtxKey{}).(string)
+	if !ok {
+		return apiV0
+	}
+	return version
+}
+
+func shapeResponse(r *http.Request, obj interface{}) interface{} {
+	return responseShapers[getVersion(r)](obj)
+}
+
+func (s *ApiServer) writeVersionHeaders(w http.ResponseWriter, version, path string) {
+	c, ok := s.Versions[version]
+	if !ok || c == nil {
+		return
+	}
+	if !c.Deprecation.IsZero() {
+		w.Header().Set("Deprecation", fmt.Sprintf("@%d", c.Deprecation.Unix()))
+		if version != latestApiVersion {
+			w.Header().Set("Link", fmt.Sprintf("</%s%s>; rel=\"successor-version\"", latestApiVersion, path))
+		}
+	}
+	if !c.Sunset.IsZero() {
+		w.Header().Set("Sunset", c.Sunset.UTC().Format(http.TimeFormat))
+	}
+}
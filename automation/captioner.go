@@ -0,0 +1,216 @@
+package automation
+
+import (
+	"bytes"
+	"context"
+	"fmt"
+	"image"
+	"image/color"
+	"image/gif"
+	"image/jpeg"
+	"image/png"
+	"mindia/exif"
+	"mindia/utils"
+	"strconv"
+	"strings"
+
+	"golang.org/x/image/draw"
+	"golang.org/x/image/font"
+	"golang.org/x/image/font/basicfont"
+	"golang.org/x/image/math/fixed"
+)
+
+type TextPosition string
+
+const (
+	TextTopLeft     TextPosition = "tl"
+	TextTopRight    TextPosition = "tr"
+	TextBottomLeft  TextPosition = "bl"
+	TextBottomRight TextPosition = "br"
+	TextCenter      TextPosition = "center"
+)
+
+const (
+	defaultTextSize  = 13
+	MaxCaptionLength = 256
+)
+
+type Caption struct {
+	Text       string       `yaml:"text"`
+	Position   TextPosition `yaml:"position"`
+	Size       int          `yaml:"size"`
+	Color      string       `yaml:"color"`
+	Background string       `yaml:"background,omitempty"`
+	Shadow     bool         `yaml:"shadow,omitempty"`
+}
+
+type CaptionerConfig struct {
+	*AutomationStepConfig `yaml:",inline"`
+	Caption               Caption `yaml:"caption"`
+}
+
+func NewCaptioner(config *CaptionerConfig) *Captioner {
+	return &Captioner{
+		AutomationStep:  *NewAutomationStep(config.AutomationStepConfig),
+		CaptionerConfig: config,
+	}
+}
+
+type Captioner struct {
+	AutomationStep
+	*CaptionerConfig `yaml:",inline"`
+}
+
+func (c *Captioner) Do(ctx context.Context) (context.Context, error) {
+	actx := ctx.Value(AutomationCtxKey{}).(AutomationCtx)
+	if actx.Body == nil {
+		return ctx, nil
+	}
+
+	body, err := DrawCaption(actx.Body, &c.Caption)
+	if err != nil {
+		return ctx, err
+	}
+	actx.Body = body
+
+	ctx = context.WithValue(ctx, AutomationCtxKey{}, actx)
+	return ctx, nil
+}
+
+func ParseHexColor(s string) (color.NRGBA, error) {
+	s = strings.TrimPrefix(s, "#")
+	if len(s) == 6 {
+		s += "ff"
+	}
+	v, err := strconv.ParseUint(s, 16, 32)
+	if err != nil || len(s) != 8 {
+		return color.NRGBA{}, fmt.Errorf("invalid color %s, expected rrggbb or rrggbbaa", s)
+	}
+	return color.NRGBA{
+		R: uint8(v >> 24),
+		G: uint8(v >> 16),
+		B: uint8(v >> 8),
+		A: uint8(v),
+	}, nil
+}
+
+func DrawCaption(body []byte, caption *Caption) ([]byte, error) {
+	img, format, err := DecodeImage(body)
+	if err != nil {
+		return nil, err
+	}
+	img = orient(img, exif.Orientation(body))
+
+	dst := image.NewRGBA(img.Bounds())
+	draw.Draw(dst, dst.Bounds(), img, img.Bounds().Min, draw.Src)
+
+	textColor := color.NRGBA{R: 0xff, G: 0xff, B: 0xff, A: 0xff}
+	if caption.Color != "" {
+		textColor, err = ParseHexColor(caption.Color)
+		if err != nil {
+			return nil, err
+		}
+	}
+
+	if len(caption.Text) > MaxCaptionLength {
+		return nil, fmt.Errorf("caption is longer than %d characters", MaxCaptionLength)
+	}
+	size := caption.Size
+	if size <= 0 {
+		size = defaultTextSize
+	}
+	text := textMask(caption.Text, caption.Position)
+	textSize := text.Rect.Size().Mul(size).Div(basicfont.Face7x13.Height)
+
+	margin := size / 2
+	padding := size / 4
+	rect := placeText(dst.Bounds(), textSize, caption.Position, margin+padding)
+
+	// only the part of the text that lands on the image is scaled
+	visible := rect.Intersect(dst.Bounds())
+	if visible.Empty() {
+		return encodeImage(dst, format)
+	}
+	mask := image.NewAlpha(visible)
+	draw.ApproxBiLinear.Scale(mask, rect, text, text.Rect, draw.Src, nil)
+
+	if caption.Background != "" {
+		background, err := ParseHexColor(caption.Background)
+		if err != nil {
+			return nil, err
+		}
+		box := rect.Inset(-padding)
+		draw.Draw(dst, box, &image.Uniform{C: background}, image.Point{}, draw.Over)
+	}
+	if caption.Shadow {
+		offset := utils.MaxInt(1, size/12)
+		shadow := &image.Uniform{C: color.NRGBA{A: 0xa0}}
+		draw.DrawMask(dst, visible.Add(image.Pt(offset, offset)), shadow, image.Point{}, mask, visible.Min, draw.Over)
+	}
+	draw.DrawMask(dst, visible, &image.Uniform{C: textColor}, image.Point{}, mask, visible.Min, draw.Over)
+
+	return encodeImage(dst, format)
+}
+
+func textMask(text string, position TextPosition) *image.Alpha {
+	face := basicfont.Face7x13
+	lines := strings.Split(text, "\n")
+
+	width := 0
+	for _, line := range lines {
+		width = utils.MaxInt(width, font.MeasureString(face, line).Ceil())
+	}
+	mask := image.NewAlpha(image.Rect(0, 0, utils.MaxInt(width, 1), face.Height*len(lines)))
+
+	d := font.Drawer{
+		Dst:  mask,
+		Src:  image.Opaque,
+		Face: face,
+	}
+	for i, line := range lines {
+		x := 0
+		lineWidth := font.MeasureString(face, line).Ceil()
+		switch position {
+		case TextTopRight, TextBottomRight:
+			x = width - lineWidth
+		case TextCenter:
+			x = (width - lineWidth) / 2
+		}
+		d.Dot = fixed.P(x, i*face.Height+face.Ascent)
+		d.DrawString(line)
+	}
+	return mask
+}
+
+func placeText(bounds image.Rectangle, size image.Point, position TextPosition, margin int) image.Rectangle {
+	var origin image.Point
+	switch position {
+	case TextTopLeft:
+		origin = image.Pt(bounds.Min.X+margin, bounds.Min.Y+margin)
+	case TextTopRight:
+		origin = image.Pt(bounds.Max.X-margin-size.X, bounds.Min.Y+margin)
+	case TextBottomLeft:
+		origin = image.Pt(bounds.Min.X+margin, bounds.Max.Y-margin-size.Y)
+	case TextCenter:
+		origin = image.Pt(bounds.Min.X+(bounds.Dx()-size.X)/2, bounds.Min.Y+(bounds.Dy()-size.Y)/2)
+	default:
+		origin = image.Pt(bounds.Max.X-margin-size.X, bounds.Max.Y-margin-size.Y)
+	}
+	return image.Rectangle{Min: origin, Max: origin.Add(size)}
+}
+
+// encodeImage keeps the source format, animated gifs come out with their first
+// frame only.
+func encodeImage(img image.Image, format string) ([]byte, error) {
+	buff := new(bytes.Buffer)
+	var err error
+	switch format {
+	case "png":
+		err = png.Encode(buff, img)
+	case "gif":
+		err = gif.Encode(buff, img, nil)
+	default:
+		err = jpeg.Encode(buff, img, &jpeg.Options{Quality: jpeg.DefaultQuality})
+	}
+	return buff.Bytes(), err
+}
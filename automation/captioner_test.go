@@ -0,0 +1,21 @@
+package automation
+
+import (
+	"bytes"
+	"image"
+	"testing"
+)
+
+func TestDrawCaptionKeepsFormat(t *testing.T) {
+	for _, format := range []string{"gif", "png"} {
+		b, err := DrawCaption(encodeTestImage(t, format), &Caption{Text: "hi", Position: TextCenter})
+		if err != nil {
+			t.Errorf("%s: got error %v", format, err)
+			continue
+		}
+		_, got, err := image.DecodeConfig(bytes.NewReader(b))
+		if err != nil || got != format {
+			t.Errorf("%s: captioned image decodes as %q with %v", format, got, err)
+		}
+	}
+}
@@ -0,0 +1,33 @@
+package automation
+
+import (
+	"bytes"
+	"errors"
+	"image"
+)
+
+var ErrImageTooLarge = errors.New("image exceeds the maximum number of pixels")
+
+var MaxPixels int64 = 50000000
+
+func CheckDimensions(body []byte) error {
+	config, _, err := image.DecodeConfig(bytes.NewReader(body))
+	if err != nil {
+		return err
+	}
+	if config.Width <= 0 || config.Height <= 0 {
+		return errors.New("image has no pixels")
+	}
+	if int64(config.Width)*int64(config.Height) > MaxPixels {
+		return ErrImageTooLarge
+	}
+	return nil
+}
+
+func DecodeImage(body []byte) (image.Image, string, error) {
+	err := CheckDimensions(body)
+	if err != nil {
+		return nil, "", err
+	}
+	return image.Decode(bytes.NewReader(body))
+}
@@ -0,0 +1,144 @@
+package automation
+
+import (
+	"bytes"
+	"context"
+	"errors"
+	"fmt"
+	"image"
+	"image/gif"
+
+	"golang.org/x/image/draw"
+)
+
+const defaultMaxFrames = 300
+
+func isGif(b []byte) bool {
+	return bytes.HasPrefix(b, []byte("GIF87a")) || bytes.HasPrefix(b, []byte("GIF89a"))
+}
+
+func (r *Resizer) resizeGif(ctx context.Context, actx AutomationCtx) (context.Context, error) {
+	err := CheckDimensions(actx.Body)
+	if err != nil {
+		return ctx, err
+	}
+
+	maxFrames := r.MaxFrames
+	if maxFrames <= 0 {
+		maxFrames = defaultMaxFrames
+	}
+	frames, err := countGifFrames(actx.Body, maxFrames+1)
+	if err != nil {
+		return ctx, err
+	}
+	if frames > maxFrames {
+		return ctx, fmt.Errorf("gif has more than %d frames", maxFrames)
+	}
+
+	g, err := gif.DecodeAll(bytes.NewReader(actx.Body))
+	if err != nil {
+		return ctx, err
+	}
+
+	width, height := r.targetSize(g.Config.Width, g.Config.Height)
+	scaleX := float64(width) / float64(g.Config.Width)
+	scaleY := float64(height) / float64(g.Config.Height)
+
+	for i, frame := range g.Image {
+		bounds := frame.Bounds()
+		rect := image.Rect(
+			int(float64(bounds.Min.X)*scaleX),
+			int(float64(bounds.Min.Y)*scaleY),
+			int(float64(bounds.Max.X)*scaleX),
+			int(float64(bounds.Max.Y)*scaleY),
+		)
+		if rect.Dx() == 0 {
+			if rect.Max.X < width {
+				rect.Max.X++
+			} else {
+				rect.Min.X--
+			}
+		}
+		if rect.Dy() == 0 {
+			if rect.Max.Y < height {
+				rect.Max.Y++
+			} else {
+				rect.Min.Y--
+			}
+		}
+		dst := image.NewPaletted(rect, frame.Palette)
+		draw.NearestNeighbor.Scale(dst, rect, frame, bounds, draw.Src, nil)
+		g.Image[i] = dst
+	}
+	g.Config.Width = width
+	g.Config.Height = height
+
+	buff := new(bytes.Buffer)
+	err = gif.EncodeAll(buff, g)
+	if err != nil {
+		return ctx, err
+	}
+	actx.Body = buff.Bytes()
+
+	ctx = context.WithValue(ctx, AutomationCtxKey{}, actx)
+	return ctx, nil
+}
+
+// countGifFrames walks the gif blocks without decoding them, stopping once limit
+// frames are found so oversized animations are never decoded.
+func countGifFrames(b []byte, limit int) (int, error) {
+	errTruncated := errors.New("gif: truncated")
+	if len(b) < 13 {
+		return 0, errTruncated
+	}
+	pos := 13
+	if b[10]&0x80 != 0 {
+		pos += 3 << (uint(b[10]&0x07) + 1)
+	}
+
+	skipSubBlocks := func() error {
+		for {
+			if pos >= len(b) {
+				return errTruncated
+			}
+			size := int(b[pos])
+			pos += 1 + size
+			if size == 0 {
+				return nil
+			}
+		}
+	}
+
+	frames := 0
+	for frames < limit {
+		if pos >= len(b) {
+			return 0, errTruncated
+		}
+		switch b[pos] {
+		case 0x21:
+			pos += 2
+			if err := skipSubBlocks(); err != nil {
+				return 0, err
+			}
+		case 0x2C:
+			if pos+10 > len(b) {
+				return 0, errTruncated
+			}
+			packed := b[pos+9]
+			pos += 10
+			if packed&0x80 != 0 {
+				pos += 3 << (uint(packed&0x07) + 1)
+			}
+			pos++
+			if err := skipSubBlocks(); err != nil {
+				return 0, err
+			}
+			frames++
+		case 0x3B:
+			return frames, nil
+		default:
+			return 0, fmt.Errorf("gif: unknown block type 0x%02x", b[pos])
+		}
+	}
+	return frames, nil
+}
@@ -0,0 +1,268 @@
+package automation
+
+import (
+	"bytes"
+	"context"
+	"encoding/binary"
+	"image"
+	"image/color"
+	"image/draw"
+	"image/png"
+	"mindia/exif"
+	"sort"
+	"sync/atomic"
+)
+
+const (
+	maxPaletteSize     = 256
+	maxQuantizeSamples = 1 << 16
+)
+
+var exifHeader = []byte("Exif\x00\x00")
+
+type OptimizerConfig struct {
+	*AutomationStepConfig `yaml:",inline"`
+	QuantizePng           bool `yaml:"quantize_png,omitempty"`
+}
+
+func NewOptimizer(config *OptimizerConfig) *Optimizer {
+	return &Optimizer{
+		AutomationStep:  *NewAutomationStep(config.AutomationStepConfig),
+		OptimizerConfig: config,
+	}
+}
+
+type Optimizer struct {
+	AutomationStep
+	*OptimizerConfig `yaml:",inline"`
+	bytesSaved       int64
+}
+
+func (o *Optimizer) BytesSaved() int64 {
+	return atomic.LoadInt64(&o.bytesSaved)
+}
+
+// Optimizers returns the optimizer steps of a and of its children.
+func (a *Automation) Optimizers() []*Optimizer {
+	var optimizers []*Optimizer
+	for _, step := range a.Steps {
+		if o, ok := step.(*Optimizer); ok {
+			optimizers = append(optimizers, o)
+		}
+		for _, child := range step.GetChildren() {
+			optimizers = append(optimizers, child.Optimizers()...)
+		}
+	}
+	return optimizers
+}
+
+func (o *Optimizer) Do(ctx context.Context) (context.Context, error) {
+	actx := ctx.Value(AutomationCtxKey{}).(AutomationCtx)
+	if actx.Body == nil {
+		return ctx, nil
+	}
+
+	optimized, err := o.optimize(actx.Body)
+	if err != nil {
+		return ctx, err
+	}
+	if optimized == nil || len(optimized) >= len(actx.Body) {
+		return ctx, nil
+	}
+	atomic.AddInt64(&o.bytesSaved, int64(len(actx.Body)-len(optimized)))
+	actx.Body = optimized
+
+	ctx = context.WithValue(ctx, AutomationCtxKey{}, actx)
+	return ctx, nil
+}
+
+var pngHeader = []byte("\x89PNG\r\n\x1a\n")
+
+func (o *Optimizer) optimize(body []byte) ([]byte, error) {
+	if len(body) > 2 && body[0] == 0xFF && body[1] == 0xD8 {
+		return stripJpegMetadata(body), nil
+	}
+
+	// gifs and the other formats are left alone without paying for a decode
+	if !bytes.HasPrefix(body, pngHeader) {
+		return nil, nil
+	}
+
+	img, _, err := DecodeImage(body)
+	if err != nil {
+		return nil, err
+	}
+
+	if o.QuantizePng {
+		if _, ok := img.(*image.Paletted); !ok {
+			img = quantize(img)
+		}
+	}
+	buff := new(bytes.Buffer)
+	encoder := png.Encoder{CompressionLevel: png.BestCompression}
+	err = encoder.Encode(buff, img)
+	return buff.Bytes(), err
+}
+
+// stripJpegMetadata drops comments and metadata segments without re-encoding the
+// image. Color profiles and Adobe segments are kept since they change how the
+// pixels decode, and so is the exif segment when it carries an orientation.
+func stripJpegMetadata(b []byte) []byte {
+	keepExif := exif.Orientation(b) != 1
+	out := bytes.NewBuffer(make([]byte, 0, len(b)))
+	out.Write(b[:2])
+
+	pos := 2
+	for pos+4 <= len(b) {
+		if b[pos] != 0xFF {
+			return nil
+		}
+		marker := b[pos+1]
+		if marker == 0xFF {
+			pos++
+			continue
+		}
+		if marker == 0x01 || (marker >= 0xD0 && marker <= 0xD8) {
+			out.Write(b[pos : pos+2])
+			pos += 2
+			continue
+		}
+		if marker == 0xDA {
+			out.Write(b[pos:])
+			return out.Bytes()
+		}
+
+		length := int(binary.BigEndian.Uint16(b[pos+2:]))
+		if length < 2 || pos+2+length > len(b) {
+			return nil
+		}
+		segment := b[pos+4 : pos+2+length]
+
+		keep := true
+		switch {
+		case marker == 0xFE:
+			keep = false
+		case marker == 0xE1:
+			keep = keepExif && bytes.HasPrefix(segment, exifHeader)
+		case marker > 0xE2 && marker <= 0xEF && marker != 0xEE:
+			keep = false
+		}
+		if keep {
+			out.Write(b[pos : pos+2+length])
+		}
+		pos += 2 + length
+	}
+	return nil
+}
+
+type colorCount struct {
+	c     [4]uint8
+	count int
+}
+
+// quantize reduces img to a palette picked by median cut over its non
+// premultiplied colors, alpha included, sampling large images.
+func quantize(img image.Image) *image.Paletted {
+	bounds := img.Bounds()
+	step := 1
+	for (bounds.Dx()/step)*(bounds.Dy()/step) > maxQuantizeSamples {
+		step++
+	}
+
+	counts := map[[4]uint8]int{}
+	for y := bounds.Min.Y; y < bounds.Max.Y; y += step {
+		for x := bounds.Min.X; x < bounds.Max.X; x += step {
+			c := color.NRGBAModel.Convert(img.At(x, y)).(color.NRGBA)
+			if c.A == 0 {
+				c = color.NRGBA{}
+			}
+			counts[[4]uint8{c.R, c.G, c.B, c.A}]++
+		}
+	}
+	colors := make([]colorCount, 0, len(counts))
+	for c, n := range counts {
+		colors = append(colors, colorCount{c: c, count: n})
+	}
+	if len(colors) == 0 {
+		return image.NewPaletted(bounds, color.Palette{color.NRGBA{}})
+	}
+
+	boxes := [][]colorCount{colors}
+	for len(boxes) < maxPaletteSize {
+		best, channel, width := -1, 0, 0
+		for i, box := range boxes {
+			if len(box) < 2 {
+				continue
+			}
+			if ch, w := widestChannel(box); w > width {
+				best, channel, width = i, ch, w
+			}
+		}
+		if best < 0 {
+			break
+		}
+
+		box := boxes[best]
+		sort.Slice(box, func(i, j int) bool {
+			return box[i].c[channel] < box[j].c[channel]
+		})
+		total := 0
+		for _, e := range box {
+			total += e.count
+		}
+		split, acc := 1, 0
+		for i, e := range box {
+			acc += e.count
+			if acc >= total/2 {
+				split = i + 1
+				break
+			}
+		}
+		if split >= len(box) {
+			split = len(box) - 1
+		}
+		boxes[best] = box[:split]
+		boxes = append(boxes, box[split:])
+	}
+
+	p := make(color.Palette, 0, len(boxes))
+	for _, box := range boxes {
+		var sum [4]int
+		total := 0
+		for _, e := range box {
+			for c := 0; c < 4; c++ {
+				sum[c] += int(e.c[c]) * e.count
+			}
+			total += e.count
+		}
+		p = append(p, color.NRGBA{
+			R: uint8(sum[0] / total),
+			G: uint8(sum[1] / total),
+			B: uint8(sum[2] / total),
+			A: uint8(sum[3] / total),
+		})
+	}
+
+	quantized := image.NewPaletted(bounds, p)
+	draw.FloydSteinberg.Draw(quantized, bounds, img, bounds.Min)
+	return quantized
+}
+
+func widestChannel(box []colorCount) (int, int) {
+	channel, width := 0, 0
+	for c := 0; c < 4; c++ {
+		lo, hi := box[0].c[c], box[0].c[c]
+		for _, e := range box {
+			if e.c[c] < lo {
+				lo = e.c[c]
+			}
+			if e.c[c] > hi {
+				hi = e.c[c]
+			}
+		}
+		if int(hi-lo) > width {
+			channel, width = c, int(hi-lo)
+		}
+	}
+	return channel, width
+}
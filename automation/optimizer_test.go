@@ -0,0 +1,32 @@
+package automation
+
+import (
+	"bytes"
+	"testing"
+)
+
+func TestOptimize(t *testing.T) {
+	scan := []byte{0xFF, 0xDA, 0x00, 0x02, 0x01, 0x02, 0xFF, 0xD9}
+	jpegWithComment := append([]byte{0xFF, 0xD8, 0xFF, 0xFE, 0x00, 0x04, 'h', 'i'}, scan...)
+
+	tests := []struct {
+		name string
+		body []byte
+		want []byte
+	}{
+		{"jpeg comment", jpegWithComment, append([]byte{0xFF, 0xD8}, scan...)},
+		{"gif", []byte("GIF89a not even decodable"), nil},
+		{"unknown", []byte("plain text"), nil},
+	}
+	o := NewOptimizer(&OptimizerConfig{AutomationStepConfig: &AutomationStepConfig{}})
+	for _, test := range tests {
+		got, err := o.optimize(test.body)
+		if err != nil {
+			t.Errorf("%s: got error %v", test.name, err)
+			continue
+		}
+		if !bytes.Equal(got, test.want) {
+			t.Errorf("%s: got %x, want %x", test.name, got, test.want)
+		}
+	}
+}
@@ -0,0 +1,179 @@
+package automation
+
+import (
+	"context"
+	"image"
+	"image/color"
+	"image/draw"
+	"mindia/exif"
+	"mindia/types"
+	"mindia/utils"
+)
+
+const maxBlurRadius = 64
+
+type RedactionMode string
+
+const (
+	RedactionBlur     RedactionMode = "blur"
+	RedactionPixelate RedactionMode = "pixelate"
+)
+
+type Redaction struct {
+	Mode   RedactionMode `yaml:"mode"`
+	Region types.Rect    `yaml:"region"`
+}
+
+type RedactorConfig struct {
+	*AutomationStepConfig `yaml:",inline"`
+	Redactions            []Redaction `yaml:"redactions"`
+}
+
+func NewRedactor(config *RedactorConfig) *Redactor {
+	return &Redactor{
+		AutomationStep: *NewAutomationStep(config.AutomationStepConfig),
+		RedactorConfig: config,
+	}
+}
+
+type Redactor struct {
+	AutomationStep
+	*RedactorConfig `yaml:",inline"`
+}
+
+func (r *Redactor) Do(ctx context.Context) (context.Context, error) {
+	actx := ctx.Value(AutomationCtxKey{}).(AutomationCtx)
+	if actx.Body == nil {
+		return ctx, nil
+	}
+
+	body, err := Redact(actx.Body, r.Redactions)
+	if err != nil {
+		return ctx, err
+	}
+	actx.Body = body
+
+	ctx = context.WithValue(ctx, AutomationCtxKey{}, actx)
+	return ctx, nil
+}
+
+func Redact(body []byte, redactions []Redaction) ([]byte, error) {
+	img, format, err := DecodeImage(body)
+	if err != nil {
+		return nil, err
+	}
+	img = orient(img, exif.Orientation(body))
+
+	dst := image.NewRGBA(img.Bounds())
+	draw.Draw(dst, dst.Bounds(), img, img.Bounds().Min, draw.Src)
+
+	for _, r := range redactions {
+		region := image.Rect(
+			int(r.Region.X),
+			int(r.Region.Y),
+			int(r.Region.X)+int(r.Region.Width),
+			int(r.Region.Y)+int(r.Region.Height),
+		).Add(dst.Bounds().Min).Intersect(dst.Bounds())
+		if region.Empty() {
+			continue
+		}
+
+		switch r.Mode {
+		case RedactionPixelate:
+			pixelate(dst, region, utils.MaxInt(8, utils.MinInt(region.Dx(), region.Dy())/10))
+		default:
+			blur(dst, region, utils.MaxInt(4, utils.MaxInt(region.Dx(), region.Dy())/20))
+		}
+	}
+
+	return encodeImage(dst, format)
+}
+
+func pixelate(img *image.RGBA, region image.Rectangle, block int) {
+	for y := region.Min.Y; y < region.Max.Y; y += block {
+		for x := region.Min.X; x < region.Max.X; x += block {
+			cell := image.Rect(x, y, x+block, y+block).Intersect(region)
+
+			var sum [4]int
+			for cy := cell.Min.Y; cy < cell.Max.Y; cy++ {
+				for cx := cell.Min.X; cx < cell.Max.X; cx++ {
+					i := img.PixOffset(cx, cy)
+					for c := 0; c < 4; c++ {
+						sum[c] += int(img.Pix[i+c])
+					}
+				}
+			}
+
+			n := cell.Dx() * cell.Dy()
+			avg := color.RGBA{
+				R: uint8(sum[0] / n),
+				G: uint8(sum[1] / n),
+				B: uint8(sum[2] / n),
+				A: uint8(sum[3] / n),
+			}
+			draw.Draw(img, cell, &image.Uniform{C: avg}, image.Point{}, draw.Src)
+		}
+	}
+}
+
+func blur(img *image.RGBA, region image.Rectangle, radius int) {
+	radius = utils.MinInt(radius, maxBlurRadius)
+	w, h := region.Dx(), region.Dy()
+
+	pix := make([]uint8, w*h*4)
+	for y := 0; y < h; y++ {
+		i := img.PixOffset(region.Min.X, region.Min.Y+y)
+		copy(pix[y*w*4:(y+1)*w*4], img.Pix[i:i+w*4])
+	}
+
+	tmp := make([]uint8, len(pix))
+	for i := 0; i < 3; i++ {
+		boxBlur(pix, tmp, w, h, radius, true)
+		boxBlur(tmp, pix, w, h, radius, false)
+	}
+
+	for y := 0; y < h; y++ {
+		i := img.PixOffset(region.Min.X, region.Min.Y+y)
+		copy(img.Pix[i:i+w*4], pix[y*w*4:(y+1)*w*4])
+	}
+}
+
+// boxBlur averages every pixel with its neighbours along one axis, keeping a
+// running sum so that the cost per pixel does not depend on the radius.
+func boxBlur(src, dst []uint8, w, h, radius int, horizontal bool) {
+	lines, length, stride, step := h, w, w*4, 4
+	if !horizontal {
+		lines, length, stride, step = w, h, 4, w*4
+	}
+
+	for l := 0; l < lines; l++ {
+		start := l * stride
+		var sum [4]int
+		n := 0
+		for k := 0; k <= radius && k < length; k++ {
+			for c := 0; c < 4; c++ {
+				sum[c] += int(src[start+k*step+c])
+			}
+			n++
+		}
+
+		for p := 0; p < length; p++ {
+			i := start + p*step
+			for c := 0; c < 4; c++ {
+				dst[i+c] = uint8(sum[c] / n)
+			}
+			if next := p + radius + 1; next < length {
+				for c := 0; c < 4; c++ {
+					sum[c] += int(src[start+next*step+c])
+				}
+				n++
+			}
+			if prev := p - radius; prev >= 0 {
+				for c := 0; c < 4; c++ {
+					sum[c] -= int(src[start+prev*step+c])
+				}
+				n--
+			}
+		}
+	}
+}
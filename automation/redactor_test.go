@@ -0,0 +1,47 @@
+package automation
+
+import (
+	"bytes"
+	"image"
+	"image/color"
+	"image/gif"
+	"image/png"
+	"math"
+	"mindia/types"
+	"testing"
+)
+
+func encodeTestImage(t *testing.T, format string) []byte {
+	t.Helper()
+	img := image.NewPaletted(image.Rect(0, 0, 16, 16), color.Palette{color.White, color.Black})
+	buff := new(bytes.Buffer)
+	var err error
+	if format == "gif" {
+		err = gif.Encode(buff, img, nil)
+	} else {
+		err = png.Encode(buff, img)
+	}
+	if err != nil {
+		t.Fatal(err)
+	}
+	return buff.Bytes()
+}
+
+func TestRedactKeepsFormat(t *testing.T) {
+	redactions := []Redaction{
+		{Mode: RedactionBlur, Region: types.Rect{X: 4, Y: 4, Width: 8, Height: 8}},
+		// the far edges overflow an int32
+		{Mode: RedactionPixelate, Region: types.Rect{X: 8, Y: 8, Width: math.MaxInt32, Height: math.MaxInt32}},
+	}
+	for _, format := range []string{"gif", "png"} {
+		b, err := Redact(encodeTestImage(t, format), redactions)
+		if err != nil {
+			t.Errorf("%s: got error %v", format, err)
+			continue
+		}
+		_, got, err := image.DecodeConfig(bytes.NewReader(b))
+		if err != nil || got != format {
+			t.Errorf("%s: redacted image decodes as %q with %v", format, got, err)
+		}
+	}
+}
@@ -6,6 +6,7 @@ import (
 	"context"
 	"image"
 	"image/jpeg"
+	"mindia/exif"
 	"mindia/types"
 
 	"golang.org/x/image/draw"
@@ -14,18 +15,21 @@ import (
 type ResizerConfig struct {
 	*AutomationStepConfig `yaml:",inline"`
 	Size                  types.Size `yaml:"size"`
+	MaxFrames             int        `yaml:"max_frames,omitempty"`
 }
 
 func NewResizer(config *ResizerConfig) *Resizer {
 	return &Resizer{
 		AutomationStep: *NewAutomationStep(config.AutomationStepConfig),
 		Size:           config.Size,
+		MaxFrames:      config.MaxFrames,
 	}
 }
 
 type Resizer struct {
 	AutomationStep
-	Size types.Size `yaml:"size"`
+	Size      types.Size `yaml:"size"`
+	MaxFrames int        `yaml:"max_frames,omitempty"`
 }
 
 func (r *Resizer) Do(ctx context.Context) (context.Context, error) {
@@ -33,20 +37,23 @@ func (r *Resizer) Do(ctx context.Context) (context.Context, error) {
 	if actx.Body == nil {
 		return ctx, nil
 	}
+	if isGif(actx.Body) {
+		return r.resizeGif(ctx, actx)
+	}
 
-	input := bytes.NewReader(actx.Body)
-	decodedInput, _ := jpeg.Decode(input)
-
-	width := decodedInput.Bounds().Max.X
-	height := decodedInput.Bounds().Max.Y
+	err := CheckDimensions(actx.Body)
+	if err != nil {
+		return ctx, err
+	}
 
-	if width < height {
-		height = int(r.Size.Height)
-		width = int(float32(r.Size.Height) / float32((height)) * float32(width))
-	} else {
-		height = int(float32(r.Size.Width) / float32((width)) * float32(height))
-		width = int(r.Size.Width)
+	input := bytes.NewReader(actx.Body)
+	decodedInput, err := jpeg.Decode(input)
+	if err != nil {
+		return ctx, err
 	}
+	decodedInput = orient(decodedInput, exif.Orientation(actx.Body))
+
+	width, height := r.targetSize(decodedInput.Bounds().Dx(), decodedInput.Bounds().Dy())
 
 	dst := image.NewRGBA(image.Rect(0, 0, width, height))
 
@@ -60,3 +67,10 @@ func (r *Resizer) Do(ctx context.Context) (context.Context, error) {
 	ctx = context.WithValue(ctx, AutomationCtxKey{}, actx)
 	return ctx, nil
 }
+
+func (r *Resizer) targetSize(width, height int) (int, int) {
+	if width < height {
+		return int(float32(r.Size.Height) / float32((height)) * float32(width)), int(r.Size.Height)
+	}
+	return int(r.Size.Width), int(float32(r.Size.Width) / float32((width)) * float32(height))
+}
@@ -0,0 +1,210 @@
+package automation
+
+import (
+	"bytes"
+	"encoding/xml"
+	"errors"
+	"fmt"
+	"io"
+	"regexp"
+	"strings"
+)
+
+const (
+	svgNamespace   = "http://www.w3.org/2000/svg"
+	xlinkNamespace = "http://www.w3.org/1999/xlink"
+)
+
+var (
+	svgRoot   = regexp.MustCompile(`(?is)^\s*(<\?xml[^>]*>\s*)?(<!--.*?-->\s*)*(<!DOCTYPE[^>]*>\s*)?<svg\b`)
+	svgCssUrl = regexp.MustCompile(`(?i)url\(\s*['"]?\s*([^'")]*)['"]?\s*\)`)
+)
+
+var ErrInvalidSvg = errors.New("invalid svg")
+
+var safeHrefPrefixes = []string{"#", "data:image/png", "data:image/jpeg", "data:image/gif", "data:image/webp"}
+
+var svgElements = toSet(
+	"svg", "g", "defs", "title", "desc", "symbol", "use", "image", "style", "switch",
+	"path", "rect", "circle", "ellipse", "line", "polyline", "polygon",
+	"text", "tspan", "textPath",
+	"linearGradient", "radialGradient", "stop", "pattern", "clipPath", "mask", "marker",
+	"filter", "feBlend", "feColorMatrix", "feComponentTransfer", "feComposite", "feConvolveMatrix",
+	"feDiffuseLighting", "feDisplacementMap", "feDistantLight", "feDropShadow", "feFlood",
+	"feFuncA", "feFuncB", "feFuncG", "feFuncR", "feGaussianBlur", "feMerge", "feMergeNode",
+	"feMorphology", "feOffset", "fePointLight", "feSpecularLighting", "feSpotLight", "feTile", "feTurbulence",
+)
+
+var svgAttributes = toSet(
+	"id", "class", "style", "lang", "transform", "href",
+	"x", "y", "x1", "y1", "x2", "y2", "cx", "cy", "r", "rx", "ry", "fx", "fy", "fr",
+	"width", "height", "d", "points", "pathLength", "viewBox", "preserveAspectRatio", "version", "baseProfile",
+	"fill", "fill-opacity", "fill-rule", "stroke", "stroke-width", "stroke-linecap", "stroke-linejoin",
+	"stroke-miterlimit", "stroke-dasharray", "stroke-dashoffset", "stroke-opacity", "opacity",
+	"color", "display", "visibility", "overflow", "clip-path", "clip-rule", "clipPathUnits",
+	"mask", "maskUnits", "maskContentUnits", "marker-start", "marker-mid", "marker-end",
+	"markerWidth", "markerHeight", "markerUnits", "refX", "refY", "orient",
+	"filter", "filterUnits", "primitiveUnits", "gradientUnits", "gradientTransform", "spreadMethod",
+	"offset", "stop-color", "stop-opacity", "patternUnits", "patternContentUnits", "patternTransform",
+	"font-family", "font-size", "font-style", "font-weight", "font-variant", "letter-spacing", "word-spacing",
+	"text-anchor", "text-decoration", "dominant-baseline", "alignment-baseline", "baseline-shift",
+	"dx", "dy", "rotate", "textLength", "lengthAdjust", "startOffset", "writing-mode",
+	"shape-rendering", "image-rendering", "color-interpolation", "color-interpolation-filters",
+	"vector-effect", "paint-order", "mix-blend-mode", "isolation", "flood-color", "flood-opacity", "lighting-color",
+	"in", "in2", "result", "stdDeviation", "mode", "operator", "k1", "k2", "k3", "k4", "type",
+	"values", "tableValues", "slope", "intercept", "amplitude", "exponent", "scale",
+	"xChannelSelector", "yChannelSelector", "baseFrequency", "numOctaves", "seed", "stitchTiles",
+	"order", "kernelMatrix", "divisor", "bias", "targetX", "targetY", "edgeMode", "kernelUnitLength",
+	"preserveAlpha", "surfaceScale", "diffuseConstant", "specularConstant", "specularExponent",
+	"azimuth", "elevation", "pointsAtX", "pointsAtY", "pointsAtZ", "limitingConeAngle", "radius",
+)
+
+func toSet(values ...string) map[string]bool {
+	set := map[string]bool{}
+	for _, v := range values {
+		set[v] = true
+	}
+	return set
+}
+
+func IsSvg(b []byte) bool {
+	return svgRoot.Match(b)
+}
+
+// SanitizeSvg re-serializes the document keeping only allowlisted elements and attributes.
+// Elements outside the allowlist are dropped along with their children, and
+// references are limited to fragments and inline raster images.
+func SanitizeSvg(b []byte) ([]byte, error) {
+	d := xml.NewDecoder(bytes.NewReader(b))
+	d.Strict = true
+
+	var (
+		out   bytes.Buffer
+		open  []xml.Name
+		skip  int
+		roots int
+	)
+	for {
+		token, err := d.RawToken()
+		if err == io.EOF {
+			break
+		}
+		if err != nil {
+			return nil, fmt.Errorf("%w: %s", ErrInvalidSvg, err)
+		}
+
+		switch t := token.(type) {
+		case xml.StartElement:
+			if len(open) == 0 {
+				roots++
+				if roots > 1 || t.Name.Space != "" || t.Name.Local != "svg" {
+					return nil, fmt.Errorf("%w: root element must be svg", ErrInvalidSvg)
+				}
+			}
+			open = append(open, t.Name)
+			if skip > 0 || t.Name.Space != "" || !svgElements[t.Name.Local] {
+				skip++
+				continue
+			}
+			writeSvgStart(&out, t)
+		case xml.EndElement:
+			if len(open) == 0 || open[len(open)-1] != t.Name {
+				return nil, fmt.Errorf("%w: unexpected closing tag %s", ErrInvalidSvg, t.Name.Local)
+			}
+			open = open[:len(open)-1]
+			if skip > 0 {
+				skip--
+				continue
+			}
+			out.WriteString("</" + t.Name.Local + ">")
+		case xml.CharData:
+			if skip > 0 || len(open) == 0 {
+				continue
+			}
+			text := []byte(t)
+			if open[len(open)-1].Local == "style" {
+				text = []byte(sanitizeCss(string(t)))
+			}
+			xml.EscapeText(&out, text)
+		}
+	}
+	if roots == 0 || len(open) > 0 {
+		return nil, fmt.Errorf("%w: incomplete document", ErrInvalidSvg)
+	}
+	return out.Bytes(), nil
+}
+
+func writeSvgStart(out *bytes.Buffer, t xml.StartElement) {
+	out.WriteString("<" + t.Name.Local)
+	for _, attr := range t.Attr {
+		name, ok := svgAttribute(attr)
+		if !ok {
+			continue
+		}
+		value := attr.Value
+		if attr.Name.Local != "href" && attr.Name.Space != "xmlns" && name != "xmlns" {
+			value = sanitizeCss(value)
+			if value == "" {
+				continue
+			}
+		}
+		out.WriteString(" " + name + `="`)
+		xml.EscapeText(out, []byte(value))
+		out.WriteString(`"`)
+	}
+	out.WriteString(">")
+}
+
+func svgAttribute(attr xml.Attr) (string, bool) {
+	switch {
+	case attr.Name.Space == "" && attr.Name.Local == "xmlns":
+		return "xmlns", attr.Value == svgNamespace
+	case attr.Name.Space == "xmlns":
+		return "xmlns:xlink", attr.Name.Local == "xlink" && attr.Value == xlinkNamespace
+	case attr.Name.Space == "xlink":
+		return "xlink:href", attr.Name.Local == "href" && isSafeHref(attr.Value)
+	case attr.Name.Space == "xml":
+		return "xml:" + attr.Name.Local, attr.Name.Local == "space" || attr.Name.Local == "lang"
+	case attr.Name.Space != "":
+		return "", false
+	case attr.Name.Local == "href":
+		return "href", isSafeHref(attr.Value)
+	}
+	return attr.Name.Local, svgAttributes[attr.Name.Local]
+}
+
+func isSafeHref(value string) bool {
+	value = strings.ToLower(strings.TrimSpace(value))
+	for _, prefix := range safeHrefPrefixes {
+		if strings.HasPrefix(value, prefix) {
+			return true
+		}
+	}
+	return false
+}
+
+// sanitizeCss drops imports, expressions and any url other than a fragment.
+// Escapes could spell those out, so css containing backslashes is dropped entirely.
+func sanitizeCss(css string) string {
+	lower := strings.ToLower(css)
+	if strings.Contains(css, `\`) || strings.Contains(lower, "@import") || strings.Contains(lower, "expression(") {
+		return ""
+	}
+	css = svgCssUrl.ReplaceAllStringFunc(css, func(url string) string {
+		ref := svgCssUrl.FindStringSubmatch(url)[1]
+		if strings.HasPrefix(strings.TrimSpace(ref), "#") {
+			return url
+		}
+		return "none"
+	})
+
+	// an unterminated url( still loads in browsers, so anything left after the replace is dropped
+	lower = strings.ToLower(css)
+	for i := strings.Index(lower, "url("); i >= 0; i = strings.Index(lower, "url(") {
+		lower = lower[i+len("url("):]
+		if !strings.HasPrefix(strings.TrimLeft(lower, ` '"`), "#") {
+			return ""
+		}
+	}
+	return css
+}
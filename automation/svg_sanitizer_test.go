@@ -0,0 +1,87 @@
+package automation
+
+import (
+	"errors"
+	"testing"
+)
+
+func TestSanitizeSvg(t *testing.T) {
+	tests := []struct {
+		name string
+		svg  string
+		want string
+		err  error
+	}{
+		{
+			name: "allowed",
+			svg:  `<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10"><rect width="10" height="10" fill="red"/></svg>`,
+			want: `<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10"><rect width="10" height="10" fill="red"></rect></svg>`,
+		},
+		{
+			name: "script",
+			svg:  `<svg><script>alert(1)</script><circle r="1"/></svg>`,
+			want: `<svg><circle r="1"></circle></svg>`,
+		},
+		{
+			name: "foreign object",
+			svg:  `<svg><foreignObject><div><img src="x"/></div></foreignObject></svg>`,
+			want: `<svg></svg>`,
+		},
+		{
+			name: "event handler",
+			svg:  `<svg onload="alert(1)"><g onclick="alert(1)" id="a"></g></svg>`,
+			want: `<svg><g id="a"></g></svg>`,
+		},
+		{
+			name: "external href",
+			svg:  `<svg xmlns:xlink="http://www.w3.org/1999/xlink"><use xlink:href="https://evil.test/a.svg#x"/><use href="#a"/></svg>`,
+			want: `<svg xmlns:xlink="http://www.w3.org/1999/xlink"><use></use><use href="#a"></use></svg>`,
+		},
+		{
+			name: "javascript href",
+			svg:  `<svg><image href=" JavaScript:alert(1)"/><image href="data:image/png;base64,AA=="/></svg>`,
+			want: `<svg><image></image><image href="data:image/png;base64,AA=="></image></svg>`,
+		},
+		{
+			name: "style urls",
+			svg:  `<svg><style>rect { fill: url(#g); background: url("https://evil.test/a.png") }</style></svg>`,
+			want: `<svg><style>rect { fill: url(#g); background: none }</style></svg>`,
+		},
+		{
+			name: "style import",
+			svg:  `<svg><style>@import url(#a);</style><rect style="fill: url(&apos;http://evil.test&apos;)"/></svg>`,
+			want: `<svg><style></style><rect style="fill: none"></rect></svg>`,
+		},
+		{
+			name: "css escapes",
+			svg:  `<svg><rect style="fill: u\72l(http://evil.test)"/></svg>`,
+			want: `<svg><rect></rect></svg>`,
+		},
+		{
+			name: "unterminated url",
+			svg:  `<svg><rect fill="url(https://evil.test"/></svg>`,
+			want: `<svg><rect></rect></svg>`,
+		},
+		{
+			name: "escaped text",
+			svg:  `<svg><text>&lt;script&gt;</text></svg>`,
+			want: `<svg><text>&lt;script&gt;</text></svg>`,
+		},
+		{name: "not svg", svg: `<html><svg></svg></html>`, err: ErrInvalidSvg},
+		{name: "namespaced root", svg: `<x:svg xmlns:x="http://www.w3.org/2000/svg"></x:svg>`, err: ErrInvalidSvg},
+		{name: "two roots", svg: `<svg></svg><svg></svg>`, err: ErrInvalidSvg},
+		{name: "unclosed", svg: `<svg><g>`, err: ErrInvalidSvg},
+		{name: "mismatched", svg: `<svg><g></svg></g>`, err: ErrInvalidSvg},
+		{name: "empty", svg: ``, err: ErrInvalidSvg},
+	}
+	for _, test := range tests {
+		got, err := SanitizeSvg([]byte(test.svg))
+		if !errors.Is(err, test.err) {
+			t.Errorf("%s: got error %v, want %v", test.name, err, test.err)
+			continue
+		}
+		if err == nil && string(got) != test.want {
+			t.Errorf("%s: got %s, want %s", test.name, got, test.want)
+		}
+	}
+}
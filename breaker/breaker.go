@@ -0,0 +1,172 @@
+package breaker
+
+import (
+	"errors"
+	"sort"
+	"sync"
+	"time"
+)
+
+const (
+	defaultThreshold = 5
+	defaultCooldown  = 30 * time.Second
+)
+
+var ErrOpen = errors.New("circuit breaker is open")
+
+type State string
+
+const (
+	StateClosed   State = "closed"
+	StateOpen     State = "open"
+	StateHalfOpen State = "half_open"
+)
+
+type BreakerConfig struct {
+	Name      string        `yaml:"name"`
+	Threshold int           `yaml:"threshold"`
+	Cooldown  time.Duration `yaml:"cooldown"`
+
+	// IsFailure tells which errors count towards tripping, all of them when nil.
+	IsFailure func(err error) bool `yaml:"-"`
+}
+
+type BreakerStatus struct {
+	Name                string    `json:"name"`
+	State               State     `json:"state"`
+	ConsecutiveFailures int       `json:"consecutive_failures"`
+	Successes           int       `json:"successes"`
+	Failures            int       `json:"failures"`
+	Rejected            int       `json:"rejected"`
+	Trips               int       `json:"trips"`
+	LastError           string    `json:"last_error,omitempty"`
+	OpenedAt            time.Time `json:"opened_at"`
+}
+
+// A Breaker trips open after Threshold consecutive failures and rejects calls
+// with ErrOpen until Cooldown elapsed, then lets a single probe through.
+type Breaker struct {
+	*BreakerConfig `yaml:",inline"`
+	status         BreakerStatus
+	probing        bool
+	mu             sync.Mutex
+}
+
+var (
+	registry   = map[string]*Breaker{}
+	registryMu sync.Mutex
+)
+
+// Register returns the breaker registered under config.Name, creating it the
+// first time, so every client of a same service shares its state.
+func Register(config *BreakerConfig) *Breaker {
+	registryMu.Lock()
+	defer registryMu.Unlock()
+
+	if b, ok := registry[config.Name]; ok {
+		return b
+	}
+	b := NewBreaker(config)
+	registry[config.Name] = b
+	return b
+}
+
+// NewBreaker returns a breaker that is not registered, for short lived clients
+// that should neither share state nor show up in Statuses.
+func NewBreaker(config *BreakerConfig) *Breaker {
+	if config.Threshold <= 0 {
+		config.Threshold = defaultThreshold
+	}
+	if config.Cooldown <= 0 {
+		config.Cooldown = defaultCooldown
+	}
+	return &Breaker{
+		BreakerConfig: config,
+		status: BreakerStatus{
+			Name:  config.Name,
+			State: StateClosed,
+		},
+	}
+}
+
+func Statuses() []*BreakerStatus {
+	registryMu.Lock()
+	breakers := make([]*Breaker, 0, len(registry))
+	for _, b := range registry {
+		breakers = append(breakers, b)
+	}
+	registryMu.Unlock()
+
+	statuses := []*BreakerStatus{}
+	for _, b := range breakers {
+		statuses = append(statuses, b.Status())
+	}
+	sort.Slice(statuses, func(i, j int) bool {
+		return statuses[i].Name < statuses[j].Name
+	})
+	return statuses
+}
+
+func (b *Breaker) Status() *BreakerStatus {
+	b.mu.Lock()
+	defer b.mu.Unlock()
+
+	status := b.status
+	return &status
+}
+
+func (b *Breaker) Do(fn func() error) error {
+	if !b.allow() {
+		return ErrOpen
+	}
+	err := fn()
+	b.record(err)
+	return err
+}
+
+func (b *Breaker) allow() bool {
+	b.mu.Lock()
+	defer b.mu.Unlock()
+
+	switch b.status.State {
+	case StateOpen:
+		if time.Since(b.status.OpenedAt) < b.Cooldown {
+			b.status.Rejected++
+			return false
+		}
+		b.status.State = StateHalfOpen
+		b.probing = true
+		return true
+	case StateHalfOpen:
+		if b.probing {
+			b.status.Rejected++
+			return false
+		}
+		b.probing = true
+	}
+	return true
+}
+
+func (b *Breaker) record(err error) {
+	b.mu.Lock()
+	defer b.mu.Unlock()
+
+	b.probing = false
+	if err == nil || (b.IsFailure != nil && !b.IsFailure(err)) {
+		b.status.Successes++
+		b.status.ConsecutiveFailures = 0
+		b.status.State = StateClosed
+		return
+	}
+
+	b.status.Failures++
+	b.status.ConsecutiveFailures++
+	b.status.LastError = err.Error()
+	if b.status.State == StateHalfOpen || b.status.ConsecutiveFailures >= b.Threshold {
+		if b.status.State != StateOpen {
+			b.status.Trips++
+		}
+		b.status.State = StateOpen
+		b.status.OpenedAt = time.Now()
+	}
+}
@@ -0,0 +1,139 @@
+package breaker
+
+import (
+	"errors"
+	"testing"
+	"time"
+)
+
+const testCooldown = 20 * time.Millisecond
+
+var (
+	errBoom    = errors.New("boom")
+	errIgnored = errors.New("ignored")
+)
+
+type step struct {
+	wait  bool
+	err   error
+	got   error
+	state State
+}
+
+func TestBreaker(t *testing.T) {
+	tests := []struct {
+		name  string
+		steps []step
+		trips int
+	}{
+		{
+			name: "stays closed under the threshold",
+			steps: []step{
+				{err: errBoom, got: errBoom, state: StateClosed},
+				{err: errBoom, got: errBoom, state: StateClosed},
+				{err: nil, got: nil, state: StateClosed},
+				{err: errBoom, got: errBoom, state: StateClosed},
+			},
+		},
+		{
+			name: "trips and rejects",
+			steps: []step{
+				{err: errBoom, got: errBoom, state: StateClosed},
+				{err: errBoom, got: errBoom, state: StateClosed},
+				{err: errBoom, got: errBoom, state: StateOpen},
+				{err: nil, got: ErrOpen, state: StateOpen},
+			},
+			trips: 1,
+		},
+		{
+			name: "closes after a successful probe",
+			steps: []step{
+				{err: errBoom, got: errBoom},
+				{err: errBoom, got: errBoom},
+				{err: errBoom, got: errBoom, state: StateOpen},
+				{wait: true, err: nil, got: nil, state: StateClosed},
+				{err: errBoom, got: errBoom, state: StateClosed},
+			},
+			trips: 1,
+		},
+		{
+			name: "reopens after a failed probe",
+			steps: []step{
+				{err: errBoom, got: errBoom},
+				{err: errBoom, got: errBoom},
+				{err: errBoom, got: errBoom, state: StateOpen},
+				{wait: true, err: errBoom, got: errBoom, state: StateOpen},
+				{err: nil, got: ErrOpen, state: StateOpen},
+			},
+			trips: 2,
+		},
+		{
+			name: "ignores errors that aren't failures",
+			steps: []step{
+				{err: errIgnored, got: errIgnored, state: StateClosed},
+				{err: errIgnored, got: errIgnored, state: StateClosed},
+				{err: errIgnored, got: errIgnored, state: StateClosed},
+			},
+		},
+	}
+	for _, test := range tests {
+		b := NewBreaker(&BreakerConfig{
+			Name:      test.name,
+			Threshold: 3,
+			Cooldown:  testCooldown,
+			IsFailure: func(err error) bool {
+				return err != errIgnored
+			},
+		})
+		for i, s := range test.steps {
+			if s.wait {
+				time.Sleep(testCooldown + 5*time.Millisecond)
+			}
+			err := b.Do(func() error {
+				return s.err
+			})
+			if err != s.got {
+				t.Errorf("%s: step %d got error %v, want %v", test.name, i, err, s.got)
+			}
+			if state := b.Status().State; s.state != "" && state != s.state {
+				t.Errorf("%s: step %d got state %s, want %s", test.name, i, state, s.state)
+			}
+		}
+		if trips := b.Status().Trips; trips != test.trips {
+			t.Errorf("%s: got %d trips, want %d", test.name, trips, test.trips)
+		}
+	}
+}
+
+func TestHalfOpenLetsASingleProbeThrough(t *testing.T) {
+	b := NewBreaker(&BreakerConfig{
+		Name:      "probe",
+		Threshold: 1,
+		Cooldown:  testCooldown,
+	})
+	b.Do(func() error {
+		return errBoom
+	})
+	time.Sleep(testCooldown + 5*time.Millisecond)
+
+	err := b.Do(func() error {
+		if err := b.Do(func() error { return nil }); err != ErrOpen {
+			t.Errorf("concurrent call during the probe got %v, want %v", err, ErrOpen)
+		}
+		return nil
+	})
+	if err != nil || b.Status().State != StateClosed {
+		t.Errorf("probe got %v and left the breaker %s", err, b.Status().State)
+	}
+}
+
+func TestRegister(t *testing.T) {
+	a := Register(&BreakerConfig{Name: "test register"})
+	b := Register(&BreakerConfig{Name: "test register"})
+	if a != b {
+		t.Error("breakers registered under a same name should be shared")
+	}
+	if NewBreaker(&BreakerConfig{Name: "test register"}) == a {
+		t.Error("NewBreaker should not return the registered breaker")
+	}
+}
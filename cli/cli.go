@@ -1,52 +1,120 @@
 package cli
 
 import (
+	"flag"
 	"fmt"
 	"io/ioutil"
-	"log"
 	"net/http"
+	"os"
 )
 
 type Cli struct {
-	Host       string
-	Port       int
-	ApiVersion string
+	Host        string
+	Port        int
+	ApiVersion  string
+	ErrorFormat string
 }
 
 type CliInput struct {
-	Host       string
-	Port       int
-	ApiVersion string
+	Host        string
+	Port        int
+	ApiVersion  string
+	ErrorFormat string
 }
 
 func NewCli(in *CliInput) *Cli {
+	errorFormat := in.ErrorFormat
+	if errorFormat == "" {
+		errorFormat = ErrorFormatText
+	}
 	return &Cli{
-		Host:       in.Host,
-		Port:       in.Port,
-		ApiVersion: in.ApiVersion,
+		Host:        in.Host,
+		Port:        in.Port,
+		ApiVersion:  in.ApiVersion,
+		ErrorFormat: errorFormat,
+	}
+}
+
+func ParseFlags(args []string) (*CliInput, []string, error) {
+	in := &CliInput{}
+	flags := flag.NewFlagSet("mindia", flag.ContinueOnError)
+	flags.StringVar(&in.Host, "host", "127.0.0.1", "api server host")
+	flags.IntVar(&in.Port, "port", 3500, "api server port")
+	flags.StringVar(&in.ApiVersion, "api-version", "v0", "api version")
+	flags.StringVar(&in.ErrorFormat, "error-format", ErrorFormatText, "error output format, text or json")
+	err := flags.Parse(args)
+	if err != nil {
+		return nil, nil, err
+	}
+	if in.ErrorFormat != ErrorFormatText && in.ErrorFormat != ErrorFormatJson {
+		return nil, nil, fmt.Errorf("invalid error format %s", in.ErrorFormat)
+	}
+	return in, flags.Args(), nil
+}
+
+// Run executes the command in args and returns the code the process should exit with.
+func Run(args []string) int {
+	in, args, err := ParseFlags(args)
+	if err != nil {
+		fmt.Fprintf(os.Stderr, "Error: %s\n", err)
+		return ExitValidation
+	}
+	c := NewCli(in)
+
+	if len(args) == 0 {
+		return c.reportError(&CliError{
+			ExitCode: ExitValidation,
+			Message:  "missing command",
+		})
 	}
+	switch args[0] {
+	case "folders":
+		err = c.ReadFolders()
+	case "policies":
+		err = c.ReadPolicies()
+	default:
+		err = &CliError{
+			ExitCode: ExitValidation,
+			Message:  fmt.Sprintf("unknown command %s", args[0]),
+		}
+	}
+	if err != nil {
+		return c.reportError(err)
+	}
+	return ExitOk
 }
 
-func (c *Cli) ReadFolders() {
-	c.printCall("/metadatas/folders")
+func (c *Cli) ReadFolders() error {
+	return c.printCall("/metadatas/folders")
 }
 
-func (c *Cli) ReadPolicies() {
-	c.printCall("/houses/garden/policies")
+func (c *Cli) ReadPolicies() error {
+	return c.printCall("/houses/garden/policies")
 }
 
-func (c *Cli) printCall(uri string) {
+func (c *Cli) printCall(uri string) error {
 	res, err := http.Get(fmt.Sprintf("http://%s:%d/%s%s", c.Host, c.Port, c.ApiVersion, uri))
 	if err != nil {
-		log.Fatalln(err)
+		return &CliError{
+			ExitCode: ExitError,
+			Message:  err.Error(),
+		}
 	}
-	printResponse(res)
+	return c.printResponse(res)
 }
 
-func printResponse(res *http.Response) {
+func (c *Cli) printResponse(res *http.Response) error {
+	defer res.Body.Close()
 	body, err := ioutil.ReadAll(res.Body)
 	if err != nil {
-		log.Fatalln(err)
+		return &CliError{
+			ExitCode: ExitError,
+			Message:  err.Error(),
+		}
+	}
+	if res.StatusCode >= 400 {
+		return responseError(res, body)
 	}
-	fmt.Printf(string(body))
+	fmt.Print(string(body))
+	return nil
 }
@@ -1,12 +1,91 @@
 package cli
 
-import "testing"
+import (
+	"net/http"
+	"net/http/httptest"
+	"net/url"
+	"strconv"
+	"testing"
+)
 
-func TestReadPolicies(t *testing.T) {
-	cli := NewCli(&CliInput{
-		Host:       "127.0.0.1",
-		Port:       3500,
+func newTestCli(t *testing.T, status int) *Cli {
+	server := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		w.WriteHeader(status)
+		w.Write([]byte("[]"))
+	}))
+	t.Cleanup(server.Close)
+
+	u, err := url.Parse(server.URL)
+	if err != nil {
+		t.Fatal(err)
+	}
+	port, err := strconv.Atoi(u.Port())
+	if err != nil {
+		t.Fatal(err)
+	}
+	return NewCli(&CliInput{
+		Host:       u.Hostname(),
+		Port:       port,
 		ApiVersion: "v0",
 	})
-	cli.ReadPolicies()
+}
+
+func TestReadPolicies(t *testing.T) {
+	cli := newTestCli(t, http.StatusOK)
+	err := cli.ReadPolicies()
+	if err != nil {
+		t.Fatal(err)
+	}
+}
+
+func TestReadPoliciesNotFound(t *testing.T) {
+	cli := newTestCli(t, http.StatusNotFound)
+	err := cli.ReadPolicies()
+	e, ok := err.(*CliError)
+	if !ok {
+		t.Fatalf("expected a cli error, got %v", err)
+	}
+	if e.Status != http.StatusNotFound || e.ExitCode != ExitNotFound {
+		t.Fatalf("got status %d and exit code %d", e.Status, e.ExitCode)
+	}
+}
+
+func TestExitCode(t *testing.T) {
+	tests := []struct {
+		status int
+		want   int
+	}{
+		{http.StatusBadRequest, ExitValidation},
+		{http.StatusRequestEntityTooLarge, ExitValidation},
+		{http.StatusUnprocessableEntity, ExitValidation},
+		{http.StatusUnauthorized, ExitAuth},
+		{http.StatusForbidden, ExitAuth},
+		{http.StatusNotFound, ExitNotFound},
+		{http.StatusTooManyRequests, ExitRateLimited},
+		{http.StatusInternalServerError, ExitServer},
+		{http.StatusServiceUnavailable, ExitServer},
+		{http.StatusConflict, ExitError},
+	}
+	for _, test := range tests {
+		if got := exitCode(test.status); got != test.want {
+			t.Errorf("exitCode(%d) = %d, want %d", test.status, got, test.want)
+		}
+	}
+}
+
+func TestRun(t *testing.T) {
+	tests := []struct {
+		args []string
+		want int
+	}{
+		{[]string{}, ExitValidation},
+		{[]string{"unknown"}, ExitValidation},
+		{[]string{"-error-format", "xml", "folders"}, ExitValidation},
+		{[]string{"-port", "1", "folders"}, ExitError},
+	}
+	for _, test := range tests {
+		if got := Run(test.args); got != test.want {
+			t.Errorf("Run(%v) = %d, want %d", test.args, got, test.want)
+		}
+	}
 }
@@ -0,0 +1,77 @@
+package cli
+
+import (
+	"encoding/json"
+	"fmt"
+	"net/http"
+	"os"
+	"strings"
+)
+
+const (
+	ExitOk          = 0
+	ExitError       = 1
+	ExitValidation  = 2
+	ExitAuth        = 3
+	ExitNotFound    = 4
+	ExitRateLimited = 5
+	ExitServer      = 6
+)
+
+const (
+	ErrorFormatText = "text"
+	ErrorFormatJson = "json"
+)
+
+type CliError struct {
+	Status   int    `json:"status,omitempty"`
+	ExitCode int    `json:"exit_code"`
+	Message  string `json:"message"`
+}
+
+func exitCode(status int) int {
+	switch {
+	case status == http.StatusBadRequest, status == http.StatusUnprocessableEntity, status == http.StatusRequestEntityTooLarge:
+		return ExitValidation
+	case status == http.StatusUnauthorized, status == http.StatusForbidden:
+		return ExitAuth
+	case status == http.StatusNotFound:
+		return ExitNotFound
+	case status == http.StatusTooManyRequests:
+		return ExitRateLimited
+	case status >= http.StatusInternalServerError:
+		return ExitServer
+	}
+	return ExitError
+}
+
+func (e *CliError) Error() string {
+	return e.Message
+}
+
+// reportError prints err on stderr in the configured format and returns its exit code.
+func (c *Cli) reportError(err error) int {
+	e, ok := err.(*CliError)
+	if !ok {
+		e = &CliError{
+			ExitCode: ExitError,
+			Message:  err.Error(),
+		}
+	}
+
+	if c.ErrorFormat == ErrorFormatJson {
+		b, _ := json.Marshal(e)
+		fmt.Fprintln(os.Stderr, string(b))
+	} else {
+		fmt.Fprintf(os.Stderr, "Error: %s\n", e.Message)
+	}
+	return e.ExitCode
+}
+
+func responseError(res *http.Response, body []byte) *CliError {
+	return &CliError{
+		Status:   res.StatusCode,
+		ExitCode: exitCode(res.StatusCode),
+		Message:  strings.TrimSpace(string(body)),
+	}
+}
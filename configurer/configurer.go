@@ -3,7 +3,10 @@ package configurer
 import (
 	"fmt"
 	"io/ioutil"
+	"mindia/apiserver"
 	"mindia/project"
+	"mindia/scheduler"
+	"os"
 
 	"gopkg.in/yaml.v3"
 )
@@ -17,6 +20,58 @@ func NewConfigurer() *Configurer {
 	return &Configurer{}
 }
 
+type persistedApiServer struct {
+	Maintenance *apiserver.MaintenanceConfig `yaml:"maintenance"`
+}
+
+type persistedConfig struct {
+	ApiServer *persistedApiServer        `yaml:"api_server"`
+	Scheduler *scheduler.SchedulerConfig `yaml:"scheduler"`
+}
+
+func readPersistedConfig() (*persistedConfig, error) {
+	config := &persistedConfig{}
+	yamlData, err := ioutil.ReadFile(fileName)
+	if os.IsNotExist(err) {
+		return config, nil
+	}
+	if err != nil {
+		return nil, err
+	}
+
+	err = yaml.Unmarshal(yamlData, config)
+	if err != nil {
+		return nil, err
+	}
+	return config, nil
+}
+
+// LoadMaintenance restores the maintenance state persisted by the previous run,
+// it must run before the config is persisted again at startup.
+func (c *Configurer) LoadMaintenance(s *apiserver.ApiServer) error {
+	config, err := readPersistedConfig()
+	if err != nil {
+		return err
+	}
+	if config.ApiServer == nil || config.ApiServer.Maintenance == nil {
+		return nil
+	}
+	return s.SetMaintenance(*config.ApiServer.Maintenance)
+}
+
+// LoadScheduler returns the scheduler block of the config, empty when there is
+// none so the scheduler defaults apply.
+func (c *Configurer) LoadScheduler() (*scheduler.SchedulerConfig, error) {
+	config, err := readPersistedConfig()
+	if err != nil {
+		return nil, err
+	}
+	if config.Scheduler == nil {
+		return &scheduler.SchedulerConfig{}, nil
+	}
+	return config.Scheduler, nil
+}
+
 func (c *Configurer) PersistConfig(p *project.Project) error {
 	yamlData, err := yaml.Marshal(p)
 	if err != nil {
@@ -0,0 +1,73 @@
+package configurer
+
+import (
+	"mindia/scheduler"
+	"os"
+	"testing"
+	"time"
+)
+
+// inTempDir runs the test from an empty dir, the configurer reading config.yaml from the working dir.
+func inTempDir(t *testing.T) {
+	wd, err := os.Getwd()
+	if err != nil {
+		t.Fatal(err)
+	}
+	err = os.Chdir(t.TempDir())
+	if err != nil {
+		t.Fatal(err)
+	}
+	t.Cleanup(func() {
+		os.Chdir(wd)
+	})
+}
+
+func TestLoadScheduler(t *testing.T) {
+	inTempDir(t)
+	config := `
+name: ae
+scheduler:
+    workers: 1
+    aging_interval: 2m
+    max_pending: 1
+`
+	err := os.WriteFile(fileName, []byte(config), 0644)
+	if err != nil {
+		t.Fatal(err)
+	}
+
+	schedulerConfig, err := NewConfigurer().LoadScheduler()
+	if err != nil {
+		t.Fatal(err)
+	}
+	s := scheduler.NewScheduler(schedulerConfig)
+	if s.Workers != 1 || s.AgingInterval != 2*time.Minute || s.MaxRetries != 3 {
+		t.Errorf("got config %+v", s.SchedulerConfig)
+	}
+
+	release := make(chan struct{})
+	defer close(release)
+	block := func(progress scheduler.Progress) error {
+		<-release
+		return nil
+	}
+	running := s.Submit(&scheduler.SubmitInput{Name: "running", Run: block})
+	for s.ReadTask(running.Id).Status != scheduler.StatusRunning {
+		time.Sleep(time.Millisecond)
+	}
+	if s.Saturated() {
+		t.Error("saturated without pending tasks")
+	}
+	s.Submit(&scheduler.SubmitInput{Name: "pending", Run: block})
+	if !s.Saturated() {
+		t.Error("max_pending from the config was not applied")
+	}
+}
+
+func TestLoadSchedulerWithoutConfig(t *testing.T) {
+	inTempDir(t)
+	schedulerConfig, err := NewConfigurer().LoadScheduler()
+	if err != nil || schedulerConfig == nil || schedulerConfig.MaxPending != 0 {
+		t.Errorf("got %+v and %v without a config file", schedulerConfig, err)
+	}
+}
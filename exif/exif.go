@@ -0,0 +1,270 @@
+package exif
+
+import (
+	"bytes"
+	"encoding/binary"
+	"errors"
+	"regexp"
+	"unicode/utf16"
+)
+
+var (
+	ErrUnsupportedFormat = errors.New("exif: unsupported format")
+	ErrInvalidTiff       = errors.New("exif: invalid tiff header")
+)
+
+const (
+	tagMake             = 0x010F
+	tagModel            = 0x0110
+	tagOrientation      = 0x0112
+	tagSoftware         = 0x0131
+	tagDateTime         = 0x0132
+	tagExifIfd          = 0x8769
+	tagGpsIfd           = 0x8825
+	tagExposureTime     = 0x829A
+	tagFNumber          = 0x829D
+	tagIso              = 0x8827
+	tagDateTimeOriginal = 0x9003
+	tagFocalLength      = 0x920A
+	tagColorSpace       = 0xA001
+	tagLensModel        = 0xA434
+
+	tagGpsLatitudeRef  = 0x0001
+	tagGpsLatitude     = 0x0002
+	tagGpsLongitudeRef = 0x0003
+	tagGpsLongitude    = 0x0004
+	tagGpsAltitudeRef  = 0x0005
+	tagGpsAltitude     = 0x0006
+)
+
+var (
+	exifHeader = []byte("Exif\x00\x00")
+	xmpHeader  = []byte("http://ns.adobe.com/xap/1.0/\x00")
+	iccHeader  = []byte("ICC_PROFILE\x00")
+
+	xmpGpsAttr    = regexp.MustCompile(`\s+exif:GPS\w+="[^"]*"`)
+	xmpGpsElement = regexp.MustCompile(`(?s)<exif:GPS(\w+)>.*?</exif:GPS\w+>`)
+)
+
+type Exif struct {
+	Make             string  `json:"make,omitempty"`
+	Model            string  `json:"model,omitempty"`
+	LensModel        string  `json:"lens_model,omitempty"`
+	Software         string  `json:"software,omitempty"`
+	DateTime         string  `json:"date_time,omitempty"`
+	DateTimeOriginal string  `json:"date_time_original,omitempty"`
+	Orientation      int     `json:"orientation,omitempty"`
+	ExposureTime     float64 `json:"exposure_time,omitempty"`
+	FNumber          float64 `json:"f_number,omitempty"`
+	Iso              int     `json:"iso,omitempty"`
+	FocalLength      float64 `json:"focal_length,omitempty"`
+	ColorSpace       string  `json:"color_space,omitempty"`
+	ColorProfile     string  `json:"color_profile,omitempty"`
+	Gps              *Gps    `json:"gps,omitempty"`
+	Xmp              string  `json:"xmp,omitempty"`
+}
+
+type Gps struct {
+	Latitude  float64 `json:"latitude"`
+	Longitude float64 `json:"longitude"`
+	Altitude  float64 `json:"altitude"`
+}
+
+func Decode(b []byte) (*Exif, error) {
+	x := &Exif{}
+	if isTiff(b) {
+		return x, x.readTiff(b)
+	}
+	if len(b) < 4 || b[0] != 0xFF || b[1] != 0xD8 {
+		return nil, ErrUnsupportedFormat
+	}
+
+	pos := 2
+	for pos+4 <= len(b) {
+		if b[pos] != 0xFF {
+			break
+		}
+		marker := b[pos+1]
+		if marker == 0xFF {
+			pos++
+			continue
+		}
+		if marker == 0x01 || (marker >= 0xD0 && marker <= 0xD8) {
+			pos += 2
+			continue
+		}
+		if marker == 0xDA || marker == 0xD9 {
+			break
+		}
+
+		length := int(binary.BigEndian.Uint16(b[pos+2:]))
+		if length < 2 || pos+2+length > len(b) {
+			break
+		}
+		segment := b[pos+4 : pos+2+length]
+
+		switch {
+		case marker == 0xE1 && bytes.HasPrefix(segment, exifHeader):
+			x.readTiff(segment[len(exifHeader):])
+		case marker == 0xE1 && bytes.HasPrefix(segment, xmpHeader):
+			x.Xmp = string(segment[len(xmpHeader):])
+		case marker == 0xE2 && bytes.HasPrefix(segment, iccHeader) && x.ColorProfile == "":
+			x.ColorProfile = iccDescription(segment[len(iccHeader):])
+		}
+
+		pos += 2 + length
+	}
+
+	return x, nil
+}
+
+func (x *Exif) readTiff(b []byte) error {
+	t, err := newTiff(b)
+	if err != nil {
+		return err
+	}
+	ifd0, _, err := t.readIfd(t.firstIfdOffset())
+	if err != nil {
+		return err
+	}
+
+	x.Make = t.readString(ifd0, tagMake)
+	x.Model = t.readString(ifd0, tagModel)
+	x.Software = t.readString(ifd0, tagSoftware)
+	x.DateTime = t.readString(ifd0, tagDateTime)
+	if v, ok := t.readUint(ifd0, tagOrientation, 0); ok {
+		x.Orientation = int(v)
+	}
+
+	if sub := t.subIfd(ifd0, tagExifIfd); sub != nil {
+		x.LensModel = t.readString(sub, tagLensModel)
+		x.DateTimeOriginal = t.readString(sub, tagDateTimeOriginal)
+		x.ExposureTime, _ = t.readRational(sub, tagExposureTime, 0)
+		x.FNumber, _ = t.readRational(sub, tagFNumber, 0)
+		x.FocalLength, _ = t.readRational(sub, tagFocalLength, 0)
+		if v, ok := t.readUint(sub, tagIso, 0); ok {
+			x.Iso = int(v)
+		}
+		if v, ok := t.readUint(sub, tagColorSpace, 0); ok {
+			switch v {
+			case 1:
+				x.ColorSpace = "sRGB"
+			case 0xFFFF:
+				x.ColorSpace = "Uncalibrated"
+			}
+		}
+	}
+
+	if sub := t.subIfd(ifd0, tagGpsIfd); sub != nil {
+		x.Gps = readGps(t, sub)
+	}
+
+	return nil
+}
+
+func readGps(t *tiff, entries ifd) *Gps {
+	lat, ok := readCoordinate(t, entries, tagGpsLatitude)
+	if !ok {
+		return nil
+	}
+	lon, ok := readCoordinate(t, entries, tagGpsLongitude)
+	if !ok {
+		return nil
+	}
+	if t.readString(entries, tagGpsLatitudeRef) == "S" {
+		lat = -lat
+	}
+	if t.readString(entries, tagGpsLongitudeRef) == "W" {
+		lon = -lon
+	}
+
+	alt, _ := t.readRational(entries, tagGpsAltitude, 0)
+	if ref, ok := t.readUint(entries, tagGpsAltitudeRef, 0); ok && ref == 1 {
+		alt = -alt
+	}
+
+	return &Gps{
+		Latitude:  lat,
+		Longitude: lon,
+		Altitude:  alt,
+	}
+}
+
+func readCoordinate(t *tiff, entries ifd, tag uint16) (float64, bool) {
+	deg, ok := t.readRational(entries, tag, 0)
+	if !ok {
+		return 0, false
+	}
+	min, _ := t.readRational(entries, tag, 1)
+	sec, _ := t.readRational(entries, tag, 2)
+	return deg + min/60 + sec/3600, true
+}
+
+func iccDescription(b []byte) string {
+	if len(b) < 2 || b[0] != 1 {
+		return ""
+	}
+	profile := b[2:]
+	if len(profile) < 132 {
+		return ""
+	}
+
+	count := int(binary.BigEndian.Uint32(profile[128:]))
+	for i := 0; i < count; i++ {
+		pos := 132 + i*12
+		if pos+12 > len(profile) {
+			return ""
+		}
+		if string(profile[pos:pos+4]) != "desc" {
+			continue
+		}
+		offset := int(binary.BigEndian.Uint32(profile[pos+4:]))
+		size := int(binary.BigEndian.Uint32(profile[pos+8:]))
+		if offset < 0 || size < 12 || offset+size > len(profile) {
+			return ""
+		}
+		return decodeIccText(profile[offset : offset+size])
+	}
+	return ""
+}
+
+func decodeIccText(tag []byte) string {
+	switch string(tag[:4]) {
+	case "desc":
+		n := int(binary.BigEndian.Uint32(tag[8:]))
+		if n > len(tag)-12 {
+			n = len(tag) - 12
+		}
+		return string(bytes.TrimRight(tag[12:12+n], "\x00"))
+	case "mluc":
+		if len(tag) < 28 {
+			return ""
+		}
+		length := int(binary.BigEndian.Uint32(tag[20:]))
+		offset := int(binary.BigEndian.Uint32(tag[24:]))
+		if offset+length > len(tag) {
+			return ""
+		}
+		text := tag[offset : offset+length]
+		runes := make([]uint16, len(text)/2)
+		for i := range runes {
+			runes[i] = binary.BigEndian.Uint16(text[i*2:])
+		}
+		return string(utf16.Decode(runes))
+	}
+	return ""
+}
+
+func (x *Exif) RedactGps() {
+	x.Gps = nil
+	x.Xmp = xmpGpsAttr.ReplaceAllString(x.Xmp, "")
+	x.Xmp = xmpGpsElement.ReplaceAllString(x.Xmp, "")
+}
+
+func Orientation(b []byte) int {
+	x, err := Decode(b)
+	if err != nil || x.Orientation < 1 || x.Orientation > 8 {
+		return 1
+	}
+	return x.Orientation
+}
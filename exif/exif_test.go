@@ -0,0 +1,116 @@
+package exif
+
+import (
+	"bytes"
+	"encoding/binary"
+	"errors"
+	"image"
+	"image/jpeg"
+	"testing"
+)
+
+type testEntry struct {
+	tag   uint16
+	typ   uint16
+	count uint32
+	value []byte
+}
+
+// buildTiff lays out a little endian tiff with a single ifd, values that don't
+// fit in an entry are appended after it.
+func buildTiff(entries []testEntry) []byte {
+	le := binary.LittleEndian
+	b := []byte("II*\x00")
+	b = le.AppendUint32(b, 8)
+	b = le.AppendUint16(b, uint16(len(entries)))
+
+	dataOffset := uint32(8 + 2 + len(entries)*12 + 4)
+	var data []byte
+	for _, e := range entries {
+		b = le.AppendUint16(b, e.tag)
+		b = le.AppendUint16(b, e.typ)
+		b = le.AppendUint32(b, e.count)
+		if len(e.value) <= 4 {
+			b = append(b, append(e.value, make([]byte, 4-len(e.value))...)...)
+			continue
+		}
+		b = le.AppendUint32(b, dataOffset+uint32(len(data)))
+		data = append(data, e.value...)
+	}
+	b = le.AppendUint32(b, 0)
+	return append(b, data...)
+}
+
+func buildJpeg(t *testing.T, tiff []byte) []byte {
+	buff := new(bytes.Buffer)
+	err := jpeg.Encode(buff, image.NewGray(image.Rect(0, 0, 8, 8)), nil)
+	if err != nil {
+		t.Fatal(err)
+	}
+	img := buff.Bytes()
+	if tiff == nil {
+		return img
+	}
+
+	segment := append(append([]byte{}, exifHeader...), tiff...)
+	b := []byte{0xFF, 0xD8, 0xFF, 0xE1}
+	b = binary.BigEndian.AppendUint16(b, uint16(len(segment)+2))
+	b = append(b, segment...)
+	return append(b, img[2:]...)
+}
+
+func short(v uint16) []byte {
+	return binary.LittleEndian.AppendUint16(nil, v)
+}
+
+func long(v uint32) []byte {
+	return binary.LittleEndian.AppendUint32(nil, v)
+}
+
+func TestDecode(t *testing.T) {
+	tiff := buildTiff([]testEntry{
+		{tagMake, typeAscii, 6, []byte("Canon\x00")},
+		{tagOrientation, typeShort, 1, short(6)},
+	})
+	jpg := buildJpeg(t, tiff)
+
+	tests := []struct {
+		name        string
+		b           []byte
+		err         error
+		maker       string
+		orientation int
+	}{
+		{"jpeg", jpg, nil, "Canon", 6},
+		{"tiff", tiff, nil, "Canon", 6},
+		{"jpeg without exif", buildJpeg(t, nil), nil, "", 0},
+		{"truncated tiff header", tiff[:6], ErrInvalidTiff, "", 0},
+		{"truncated tiff ifd", tiff[:20], ErrInvalidTiff, "", 0},
+		{"truncated exif segment", jpg[:30], nil, "", 0},
+		{"png", []byte("\x89PNG\r\n\x1a\n"), ErrUnsupportedFormat, "", 0},
+		{"empty", nil, ErrUnsupportedFormat, "", 0},
+	}
+	for _, test := range tests {
+		x, err := Decode(test.b)
+		if !errors.Is(err, test.err) {
+			t.Errorf("%s: got error %v, want %v", test.name, err, test.err)
+			continue
+		}
+		if err != nil {
+			continue
+		}
+		if x.Make != test.maker || x.Orientation != test.orientation {
+			t.Errorf("%s: got make %q and orientation %d", test.name, x.Make, x.Orientation)
+		}
+	}
+}
+
+func TestDecodeTruncated(t *testing.T) {
+	jpg := buildJpeg(t, buildTiff([]testEntry{
+		{tagMake, typeAscii, 6, []byte("Canon\x00")},
+		{tagExifIfd, typeLong, 1, long(1 << 30)},
+	}))
+	for n := range jpg {
+		Decode(jpg[:n])
+	}
+}
@@ -0,0 +1,101 @@
+package exif
+
+import (
+	"bytes"
+	"errors"
+	"image/jpeg"
+)
+
+var ErrNoPreview = errors.New("exif: no embedded jpeg preview")
+
+const (
+	tagSubIfds         = 0x014A
+	tagCompression     = 0x0103
+	tagStripOffsets    = 0x0111
+	tagStripByteCounts = 0x0117
+	tagJpegOffset      = 0x0201
+	tagJpegLength      = 0x0202
+
+	compressionJpeg = 6
+	maxIfds         = 32
+)
+
+// Preview returns the largest baseline jpeg embedded in a tiff based raw file
+// such as CR2, NEF, ARW or DNG.
+func Preview(b []byte) ([]byte, error) {
+	t, err := newTiff(b)
+	if err != nil {
+		return nil, err
+	}
+
+	var (
+		best    []byte
+		visited = map[uint32]bool{}
+		queue   = []uint32{t.firstIfdOffset()}
+	)
+	for len(queue) > 0 && len(visited) < maxIfds {
+		offset := queue[0]
+		queue = queue[1:]
+		if offset == 0 || visited[offset] {
+			continue
+		}
+		visited[offset] = true
+
+		entries, next, err := t.readIfd(offset)
+		if err != nil {
+			continue
+		}
+		queue = append(queue, next)
+		if e, ok := entries[tagSubIfds]; ok {
+			for i := uint32(0); i < e.count; i++ {
+				if sub, ok := t.readUint(entries, tagSubIfds, i); ok {
+					queue = append(queue, sub)
+				}
+			}
+		}
+
+		for _, candidate := range t.previewCandidates(entries) {
+			if len(candidate) > len(best) && isBaselineJpeg(candidate) {
+				best = candidate
+			}
+		}
+	}
+
+	if best == nil {
+		return nil, ErrNoPreview
+	}
+	return best, nil
+}
+
+func (t *tiff) previewCandidates(entries ifd) [][]byte {
+	var candidates [][]byte
+	if offset, ok := t.readUint(entries, tagJpegOffset, 0); ok {
+		if length, ok := t.readUint(entries, tagJpegLength, 0); ok {
+			candidates = append(candidates, t.slice(offset, length))
+		}
+	}
+	if compression, ok := t.readUint(entries, tagCompression, 0); ok && compression == compressionJpeg {
+		offset, ok := t.readUint(entries, tagStripOffsets, 0)
+		length, ok2 := t.readUint(entries, tagStripByteCounts, 0)
+		if ok && ok2 {
+			candidates = append(candidates, t.slice(offset, length))
+		}
+	}
+	return candidates
+}
+
+func (t *tiff) slice(offset, length uint32) []byte {
+	end := int64(offset) + int64(length)
+	if length == 0 || end > int64(len(t.b)) {
+		return nil
+	}
+	return t.b[offset:end]
+}
+
+func isBaselineJpeg(b []byte) bool {
+	if len(b) < 4 || b[0] != 0xFF || b[1] != 0xD8 {
+		return false
+	}
+	_, err := jpeg.DecodeConfig(bytes.NewReader(b))
+	return err == nil
+}
@@ -0,0 +1,45 @@
+package exif
+
+import (
+	"bytes"
+	"errors"
+	"testing"
+)
+
+func TestPreview(t *testing.T) {
+	preview := buildJpeg(t, nil)
+	entries := []testEntry{
+		{tagJpegOffset, typeLong, 1, nil},
+		{tagJpegLength, typeLong, 1, long(uint32(len(preview)))},
+	}
+	// the preview goes right after the ifd
+	entries[0].value = long(uint32(len(buildTiff(entries))))
+	raw := append(buildTiff(entries), preview...)
+
+	tests := []struct {
+		name string
+		b    []byte
+		err  error
+	}{
+		{"raw", raw, nil},
+		{"truncated preview", raw[:len(raw)-1], ErrNoPreview},
+		{"no preview", buildTiff(nil), ErrNoPreview},
+		{"truncated ifd", raw[:12], ErrNoPreview},
+		{"truncated header", raw[:4], ErrInvalidTiff},
+		{"not a tiff", preview, ErrInvalidTiff},
+	}
+	for _, test := range tests {
+		b, err := Preview(test.b)
+		if !errors.Is(err, test.err) {
+			t.Errorf("%s: got error %v, want %v", test.name, err, test.err)
+			continue
+		}
+		if err == nil && !bytes.Equal(b, preview) {
+			t.Errorf("%s: got a %d bytes preview, want %d", test.name, len(b), len(preview))
+		}
+	}
+
+	for n := range raw {
+		Preview(raw[:n])
+	}
+}
@@ -0,0 +1,165 @@
+package exif
+
+import (
+	"encoding/binary"
+	"strings"
+)
+
+const (
+	typeByte      = 1
+	typeAscii     = 2
+	typeShort     = 3
+	typeLong      = 4
+	typeRational  = 5
+	typeUndefined = 7
+)
+
+var typeSizes = map[uint16]int64{
+	1:  1,
+	2:  1,
+	3:  2,
+	4:  4,
+	5:  8,
+	6:  1,
+	7:  1,
+	8:  2,
+	9:  4,
+	10: 8,
+	11: 4,
+	12: 8,
+}
+
+type tiff struct {
+	b     []byte
+	order binary.ByteOrder
+}
+
+type entry struct {
+	typ   uint16
+	count uint32
+	value []byte
+}
+
+type ifd map[uint16]*entry
+
+func isTiff(b []byte) bool {
+	return len(b) >= 4 && (string(b[:4]) == "II*\x00" || string(b[:4]) == "MM\x00*")
+}
+
+func newTiff(b []byte) (*tiff, error) {
+	if len(b) < 8 {
+		return nil, ErrInvalidTiff
+	}
+	t := &tiff{b: b}
+	switch string(b[:2]) {
+	case "II":
+		t.order = binary.LittleEndian
+	case "MM":
+		t.order = binary.BigEndian
+	default:
+		return nil, ErrInvalidTiff
+	}
+	if t.order.Uint16(b[2:]) != 42 {
+		return nil, ErrInvalidTiff
+	}
+	return t, nil
+}
+
+func (t *tiff) firstIfdOffset() uint32 {
+	return t.order.Uint32(t.b[4:])
+}
+
+func (t *tiff) readIfd(offset uint32) (ifd, uint32, error) {
+	start := int64(offset) + 2
+	if start > int64(len(t.b)) {
+		return nil, 0, ErrInvalidTiff
+	}
+	n := int64(t.order.Uint16(t.b[offset:]))
+	end := start + n*12
+	if end > int64(len(t.b)) {
+		return nil, 0, ErrInvalidTiff
+	}
+
+	entries := ifd{}
+	for i := int64(0); i < n; i++ {
+		e := t.b[start+i*12 : start+i*12+12]
+		tag := t.order.Uint16(e[0:])
+		typ := t.order.Uint16(e[2:])
+		count := t.order.Uint32(e[4:])
+
+		size := typeSizes[typ] * int64(count)
+		if size == 0 {
+			continue
+		}
+		var value []byte
+		if size <= 4 {
+			value = e[8 : 8+size]
+		} else {
+			valueOffset := int64(t.order.Uint32(e[8:]))
+			if valueOffset+size > int64(len(t.b)) {
+				continue
+			}
+			value = t.b[valueOffset : valueOffset+size]
+		}
+		entries[tag] = &entry{
+			typ:   typ,
+			count: count,
+			value: value,
+		}
+	}
+
+	var next uint32
+	if end+4 <= int64(len(t.b)) {
+		next = t.order.Uint32(t.b[end:])
+	}
+	return entries, next, nil
+}
+
+func (t *tiff) subIfd(entries ifd, tag uint16) ifd {
+	offset, ok := t.readUint(entries, tag, 0)
+	if !ok {
+		return nil
+	}
+	sub, _, err := t.readIfd(offset)
+	if err != nil {
+		return nil
+	}
+	return sub
+}
+
+func (t *tiff) readString(entries ifd, tag uint16) string {
+	e, ok := entries[tag]
+	if !ok || (e.typ != typeAscii && e.typ != typeUndefined && e.typ != typeByte) {
+		return ""
+	}
+	return strings.TrimRight(string(e.value), "\x00 ")
+}
+
+func (t *tiff) readUint(entries ifd, tag uint16, i uint32) (uint32, bool) {
+	e, ok := entries[tag]
+	if !ok || i >= e.count {
+		return 0, false
+	}
+	switch e.typ {
+	case typeByte, typeUndefined:
+		return uint32(e.value[i]), true
+	case typeShort:
+		return uint32(t.order.Uint16(e.value[i*2:])), true
+	case typeLong:
+		return t.order.Uint32(e.value[i*4:]), true
+	}
+	return 0, false
+}
+
+func (t *tiff) readRational(entries ifd, tag uint16, i uint32) (float64, bool) {
+	e, ok := entries[tag]
+	if !ok || e.typ != typeRational || i >= e.count {
+		return 0, false
+	}
+	num := t.order.Uint32(e.value[i*8:])
+	den := t.order.Uint32(e.value[i*8+4:])
+	if den == 0 {
+		return 0, false
+	}
+	return float64(num) / float64(den), true
+}
@@ -0,0 +1,147 @@
+package folder
+
+import (
+	"archive/tar"
+	"archive/zip"
+	"compress/gzip"
+	"fmt"
+	"io"
+	"mindia/automation"
+	"mindia/storage"
+	"mindia/types"
+	"path/filepath"
+	"strings"
+	"time"
+)
+
+const (
+	ArchiveZip   = "zip"
+	ArchiveTarGz = "tar.gz"
+)
+
+type archiveWriter interface {
+	add(name string, bytes []byte) error
+	Close() error
+}
+
+type zipArchive struct {
+	*zip.Writer
+}
+
+func (a *zipArchive) add(name string, bytes []byte) error {
+	w, err := a.Create(name)
+	if err != nil {
+		return err
+	}
+	_, err = w.Write(bytes)
+	return err
+}
+
+type tarGzArchive struct {
+	*tar.Writer
+	gz *gzip.Writer
+}
+
+func (a *tarGzArchive) add(name string, bytes []byte) error {
+	err := a.WriteHeader(&tar.Header{
+		Name:    name,
+		Mode:    0644,
+		Size:    int64(len(bytes)),
+		ModTime: time.Now(),
+	})
+	if err != nil {
+		return err
+	}
+	_, err = a.Write(bytes)
+	return err
+}
+
+func (a *tarGzArchive) Close() error {
+	err := a.Writer.Close()
+	if err != nil {
+		return err
+	}
+	return a.gz.Close()
+}
+
+func newArchiveWriter(w io.Writer, format string) (archiveWriter, error) {
+	switch format {
+	case ArchiveZip:
+		return &zipArchive{zip.NewWriter(w)}, nil
+	case ArchiveTarGz:
+		gz := gzip.NewWriter(w)
+		return &tarGzArchive{tar.NewWriter(gz), gz}, nil
+	}
+	return nil, fmt.Errorf("unsupported archive format %s", format)
+}
+
+type ArchiveInput struct {
+	Format string
+	Size   *types.Size
+}
+
+func (f *Folder) WriteArchive(w io.Writer, in *ArchiveInput) error {
+	archive, err := newArchiveWriter(w, in.Format)
+	if err != nil {
+		return err
+	}
+
+	files, err := f.Storage.ReadAll(&storage.ReadAllInput{Dir: f.Dir})
+	if err != nil {
+		return err
+	}
+
+	for _, file := range withoutSidecars(files) {
+		var bytes []byte
+		if in.Size != nil && isJpeg(file.Name) {
+			name := file.Name
+			ops := fmt.Sprintf("resize:%dx%d", in.Size.Width, in.Size.Height)
+			bytes, err = f.Transform(name, ops, func(b []byte) ([]byte, error) {
+				return resize(name, b, *in.Size)
+			})
+		} else {
+			bytes, err = f.Download(file.Name)
+		}
+		if err != nil {
+			return err
+		}
+		err = archive.add(file.Name, bytes)
+		if err != nil {
+			return err
+		}
+	}
+
+	return archive.Close()
+}
+
+func isJpeg(name string) bool {
+	ext := strings.ToLower(filepath.Ext(name))
+	return ext == ".jpg" || ext == ".jpeg"
+}
+
+func resize(name string, bytes []byte, size types.Size) ([]byte, error) {
+	var out []byte
+
+	a := automation.NewAutomation(&automation.AutomationConfig{
+		Steps: []automation.AutomationDoer{
+			automation.NewResizer(&automation.ResizerConfig{
+				AutomationStepConfig: &automation.AutomationStepConfig{},
+				Size:                 size,
+			}),
+		},
+	})
+	sinker := automation.Sinker{
+		SinkerConfig: &automation.SinkerConfig{
+			Sink: func(actx automation.AutomationCtx) {
+				out = actx.Body
+			},
+		},
+	}
+
+	actx := automation.AutomationCtx{
+		Name: name,
+		Body: bytes,
+	}
+	_, err := a.Run(actx, nil, nil, &sinker)
+	return out, err
+}
@@ -0,0 +1,148 @@
+package folder
+
+import (
+	"crypto/sha256"
+	"encoding/hex"
+	"encoding/json"
+	"mindia/storage"
+	"mindia/utils"
+	"time"
+)
+
+const dedupDir = ".dedup"
+
+type recentUpload struct {
+	Files      []string  `json:"files"`
+	UploadedAt time.Time `json:"uploaded_at"`
+}
+
+func hashUpload(bytes []byte) string {
+	sum := sha256.Sum256(bytes)
+	return hex.EncodeToString(sum[:])
+}
+
+func (f *Folder) dedupDir() string {
+	return utils.JoinPath(f.Dir, dedupDir)
+}
+
+func (f *Folder) findRecentUpload(hash string) []string {
+	if f.DedupWindow <= 0 {
+		return nil
+	}
+
+	f.mu.Lock()
+	for h, u := range f.recentUploads {
+		if time.Since(u.UploadedAt) > f.DedupWindow {
+			delete(f.recentUploads, h)
+		}
+	}
+	u, ok := f.recentUploads[hash]
+	f.mu.Unlock()
+	if !ok && f.PersistDedup {
+		u = f.readDedupRecord(hash)
+	}
+	if u == nil {
+		return nil
+	}
+
+	// the files may have been deleted or moved since, possibly by another instance
+	if !f.filesExist(u.Files) {
+		f.forgetUpload(hash)
+		return nil
+	}
+
+	f.mu.Lock()
+	f.recentUploads[hash] = u
+	f.mu.Unlock()
+	return u.Files
+}
+
+func (f *Folder) filesExist(files []string) bool {
+	for _, file := range files {
+		exists, err := f.Storage.DoesExist(&storage.DoesExistInput{
+			Dir:  f.Dir,
+			Name: file,
+		})
+		if err != nil || !exists {
+			return false
+		}
+	}
+	return true
+}
+
+func (f *Folder) forgetUpload(hash string) {
+	f.mu.Lock()
+	delete(f.recentUploads, hash)
+	f.mu.Unlock()
+
+	if f.PersistDedup {
+		f.Storage.Delete(&storage.DeleteInput{
+			Dir:  f.dedupDir(),
+			Name: hash + ".json",
+		})
+	}
+}
+
+// readDedupRecord looks up uploads remembered by the other instances sharing
+// the storage, expired records are deleted on the way.
+func (f *Folder) readDedupRecord(hash string) *recentUpload {
+	b, err := f.Storage.Download(&storage.DonwloadInput{
+		Dir:  f.dedupDir(),
+		Name: hash + ".json",
+	})
+	if err != nil || b == nil {
+		return nil
+	}
+	u := &recentUpload{}
+	err = json.Unmarshal(b, u)
+	if err != nil || time.Since(u.UploadedAt) > f.DedupWindow {
+		f.Storage.Delete(&storage.DeleteInput{
+			Dir:  f.dedupDir(),
+			Name: hash + ".json",
+		})
+		return nil
+	}
+	return u
+}
+
+func (f *Folder) rememberUpload(hash string, files []string) {
+	if f.DedupWindow <= 0 || len(files) == 0 {
+		return
+	}
+
+	u := &recentUpload{
+		Files:      files,
+		UploadedAt: time.Now(),
+	}
+	f.mu.Lock()
+	f.recentUploads[hash] = u
+	f.mu.Unlock()
+
+	if !f.PersistDedup {
+		return
+	}
+	b, err := json.Marshal(u)
+	if err != nil {
+		return
+	}
+	f.Storage.Upload(&storage.UploadInput{
+		Dir:   f.dedupDir(),
+		Name:  hash + ".json",
+		Bytes: b,
+	})
+}
+
+func (f *Folder) purgeDedupRecords() {
+	if !f.PersistDedup {
+		return
+	}
+	files, err := f.Storage.ReadAll(&storage.ReadAllInput{
+		Dir: f.dedupDir(),
+	})
+	if err != nil {
+		return
+	}
+	for _, file := range files {
+		f.readDedupRecord(utils.NameWithoutExt(file.Name))
+	}
+}
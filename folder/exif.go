@@ -0,0 +1,80 @@
+package folder
+
+import (
+	"bytes"
+	"encoding/json"
+	"image"
+	"mindia/exif"
+	"mindia/storage"
+	"mindia/types"
+)
+
+func exifName(name string) string {
+	return name + ".exif.json"
+}
+
+func (f *Folder) ReadExif(name string) (*exif.Exif, error) {
+	b, err := f.Storage.Download(&storage.DonwloadInput{
+		Dir:  f.Dir,
+		Name: exifName(name),
+	})
+	if err != nil {
+		return nil, err
+	}
+	if b != nil {
+		x := &exif.Exif{}
+		return x, json.Unmarshal(b, x)
+	}
+
+	b, err = f.Download(name)
+	if err != nil {
+		return nil, err
+	}
+	return f.storeExif(name, b)
+}
+
+func (f *Folder) storeExif(name string, b []byte) (*exif.Exif, error) {
+	x, err := exif.Decode(b)
+	if err != nil {
+		return nil, err
+	}
+	if f.RedactGps {
+		x.RedactGps()
+	}
+
+	data, err := json.Marshal(x)
+	if err != nil {
+		return nil, err
+	}
+	err = f.Storage.Upload(&storage.UploadInput{
+		Dir:   f.Dir,
+		Name:  exifName(name),
+		Bytes: data,
+	})
+	return x, err
+}
+
+func (f *Folder) ReadDimensions(name string) (*types.Dimensions, error) {
+	b, err := f.Download(name)
+	if err != nil {
+		return nil, err
+	}
+	config, _, err := image.DecodeConfig(bytes.NewReader(b))
+	if err != nil {
+		return nil, err
+	}
+
+	d := &types.Dimensions{
+		Size: types.Size{
+			Width:  int32(config.Width),
+			Height: int32(config.Height),
+		},
+		RawWidth:    int32(config.Width),
+		RawHeight:   int32(config.Height),
+		Orientation: exif.Orientation(b),
+	}
+	if d.Orientation >= 5 {
+		d.Width, d.Height = d.Height, d.Width
+	}
+	return d, nil
+}
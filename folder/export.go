@@ -0,0 +1,235 @@
+package folder
+
+import (
+	"archive/zip"
+	"bytes"
+	"crypto/sha256"
+	"encoding/hex"
+	"encoding/json"
+	"fmt"
+	"mindia/scheduler"
+	"mindia/storage"
+	"mindia/utils"
+	"time"
+
+	"github.com/google/uuid"
+)
+
+const defaultArchiveMaxBytes = 100 << 20
+
+type Export struct {
+	Id        string    `json:"id"`
+	TaskId    string    `json:"task_id"`
+	Dir       string    `json:"dir"`
+	Status    JobStatus `json:"status"`
+	Archives  []string  `json:"archives"`
+	Manifest  string    `json:"manifest,omitempty"`
+	Error     string    `json:"error,omitempty"`
+	CreatedAt time.Time `json:"created_at"`
+}
+
+type ExportInput struct {
+	Prefix          string             `json:"prefix"`
+	ArchiveMaxBytes int                `json:"archive_max_bytes"`
+	Priority        scheduler.Priority `json:"priority,omitempty"`
+	DependsOn       []string           `json:"depends_on,omitempty"`
+}
+
+type ExportManifest struct {
+	Id        string                 `json:"id"`
+	Dir       string                 `json:"dir"`
+	CreatedAt time.Time              `json:"created_at"`
+	Files     []*ExportManifestEntry `json:"files"`
+}
+
+type ExportManifestEntry struct {
+	Name    string `json:"name"`
+	Archive string `json:"archive"`
+	Size    int    `json:"size"`
+	Sha256  string `json:"sha256"`
+}
+
+func exportDir(id string) string {
+	return utils.JoinPath("exports", id)
+}
+
+func (f *Folder) Export(in *ExportInput) *Export {
+	if in.ArchiveMaxBytes <= 0 {
+		in.ArchiveMaxBytes = defaultArchiveMaxBytes
+	}
+
+	e := &Export{
+		Id:        uuid.New().String(),
+		Dir:       f.Dir,
+		Status:    JobPending,
+		Archives:  []string{},
+		CreatedAt: time.Now(),
+	}
+
+	f.mu.Lock()
+	var finished []*jobRef
+	for id, previous := range f.exports {
+		if previous.Status == JobDone || previous.Status == JobFailed {
+			finished = append(finished, &jobRef{id: id, createdAt: previous.CreatedAt})
+		}
+	}
+	for _, id := range prunedJobs(finished) {
+		delete(f.exports, id)
+	}
+	f.exports[e.Id] = e
+	f.mu.Unlock()
+
+	task := f.Scheduler.Submit(&scheduler.SubmitInput{
+		Name:      "export",
+		Group:     f.Dir,
+		Priority:  in.Priority,
+		DependsOn: in.DependsOn,
+		Run: func(progress scheduler.Progress) error {
+			return f.runExport(e, in, progress)
+		},
+	})
+
+	f.mu.Lock()
+	e.TaskId = task.Id
+	f.mu.Unlock()
+
+	return f.ReadExport(e.Id)
+}
+
+func (f *Folder) ReadExport(id string) *Export {
+	f.mu.Lock()
+	defer f.mu.Unlock()
+
+	e, ok := f.exports[id]
+	if !ok {
+		return nil
+	}
+	snapshot := *e
+	return &snapshot
+}
+
+func (f *Folder) runExport(e *Export, in *ExportInput, progress scheduler.Progress) error {
+	f.mu.Lock()
+	e.Status = JobRunning
+	e.Error = ""
+	f.mu.Unlock()
+
+	archives, manifest, err := f.writeExport(e, in, progress)
+
+	f.mu.Lock()
+	defer f.mu.Unlock()
+
+	if err != nil {
+		e.Status = JobFailed
+		e.Error = err.Error()
+		return err
+	}
+	e.Status = JobDone
+	e.Archives = archives
+	e.Manifest = manifest
+	return nil
+}
+
+func (f *Folder) writeExport(e *Export, in *ExportInput, progress scheduler.Progress) ([]string, string, error) {
+	files, err := f.ReadPrefix(in.Prefix)
+	if err != nil {
+		return nil, "", err
+	}
+	files = withoutSidecars(files)
+
+	var (
+		dir      = utils.JoinPath(f.Dir, exportDir(e.Id))
+		buf      = new(bytes.Buffer)
+		archive  = zip.NewWriter(buf)
+		size     = 0
+		count    = 0
+		archives = []string{}
+		manifest = &ExportManifest{
+			Id:        e.Id,
+			Dir:       f.Dir,
+			CreatedAt: e.CreatedAt,
+			Files:     []*ExportManifestEntry{},
+		}
+	)
+
+	flush := func() error {
+		err := archive.Close()
+		if err != nil {
+			return err
+		}
+		name := fmt.Sprintf("archive-%d.zip", len(archives)+1)
+		err = f.Storage.Upload(&storage.UploadInput{
+			Dir:   dir,
+			Name:  name,
+			Bytes: buf.Bytes(),
+		})
+		if err != nil {
+			return err
+		}
+		archives = append(archives, utils.JoinPath(exportDir(e.Id), name))
+		buf = new(bytes.Buffer)
+		archive = zip.NewWriter(buf)
+		size = 0
+		count = 0
+		return nil
+	}
+
+	for n, file := range files {
+		progress(n, len(files))
+		body, err := f.Download(file.Name)
+		if err != nil {
+			return nil, "", err
+		}
+		if body == nil {
+			continue
+		}
+
+		if count > 0 && size+len(body) > in.ArchiveMaxBytes {
+			err = flush()
+			if err != nil {
+				return nil, "", err
+			}
+		}
+
+		w, err := archive.Create(file.Name)
+		if err != nil {
+			return nil, "", err
+		}
+		_, err = w.Write(body)
+		if err != nil {
+			return nil, "", err
+		}
+		size += len(body)
+		count++
+
+		sum := sha256.Sum256(body)
+		manifest.Files = append(manifest.Files, &ExportManifestEntry{
+			Name:    file.Name,
+			Archive: utils.JoinPath(exportDir(e.Id), fmt.Sprintf("archive-%d.zip", len(archives)+1)),
+			Size:    len(body),
+			Sha256:  hex.EncodeToString(sum[:]),
+		})
+	}
+
+	if count > 0 || len(archives) == 0 {
+		err = flush()
+		if err != nil {
+			return nil, "", err
+		}
+	}
+
+	data, err := json.MarshalIndent(manifest, "", "	")
+	if err != nil {
+		return nil, "", err
+	}
+	err = f.Storage.Upload(&storage.UploadInput{
+		Dir:   dir,
+		Name:  "manifest.json",
+		Bytes: data,
+	})
+	if err != nil {
+		return nil, "", err
+	}
+
+	return archives, utils.JoinPath(exportDir(e.Id), "manifest.json"), nil
+}
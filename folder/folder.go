@@ -1,12 +1,17 @@
 package folder
 
 import (
+	"errors"
 	"fmt"
 	"mindia/automation"
+	"mindia/exif"
 	"mindia/policy"
+	"mindia/scheduler"
 	"mindia/storage"
 	"mindia/types"
 	"mindia/utils"
+	"strings"
+	"sync"
 	"time"
 )
 
@@ -16,28 +21,95 @@ type Automation struct {
 }
 
 type FolderConfig struct {
-	Dir         string           `yaml:"dir"`
-	Storage     storage.Storage  `yaml:"storage"`
-	Backup      storage.Storage  `yaml:"backup,omitempty"`
-	Automations []*Automation    `yaml:"automations"`
-	Policies    []*policy.Policy `yaml:"policies"`
+	Dir                    string               `yaml:"dir"`
+	Storage                storage.Storage      `yaml:"storage"`
+	Backup                 storage.Storage      `yaml:"backup,omitempty"`
+	Automations            []*Automation        `yaml:"automations"`
+	Policies               []*policy.Policy     `yaml:"policies"`
+	DedupWindow            time.Duration        `yaml:"dedup_window,omitempty"`
+	PersistDedup           bool                 `yaml:"persist_dedup,omitempty"`
+	RedactGps              bool                 `yaml:"redact_gps,omitempty"`
+	TransformCacheMaxBytes int64                `yaml:"transform_cache_max_bytes,omitempty"`
+	Gc                     *GcConfig            `yaml:"gc,omitempty"`
+	Scheduler              *scheduler.Scheduler `yaml:"-"`
 }
 
 type Folder struct {
-	*FolderConfig `yaml:",inline"`
+	*FolderConfig       `yaml:",inline"`
+	recentUploads       map[string]*recentUpload
+	exports             map[string]*Export
+	imports             map[string]*Import
+	integrityEvents     []*IntegrityEvent
+	transformCache      map[string]*transformCacheEntry
+	transformCacheBytes int64
+	orphansSeenAt       map[string]time.Time
+	sidecarTasks        map[string]bool
+	gcReport            *GcReport
+	stop                chan struct{}
+	stopOnce            sync.Once
+	mu                  sync.Mutex
+}
+
+type UploadOutput struct {
+	Files        []string `json:"files"`
+	Originals    []string `json:"originals,omitempty"`
+	Deduplicated bool     `json:"deduplicated"`
 }
 
 func NewFolder(config *FolderConfig) *Folder {
+	if config.Scheduler == nil {
+		config.Scheduler = scheduler.Default()
+	}
 	f := &Folder{
-		FolderConfig: config,
+		FolderConfig:   config,
+		recentUploads:  map[string]*recentUpload{},
+		exports:        map[string]*Export{},
+		imports:        map[string]*Import{},
+		transformCache: map[string]*transformCacheEntry{},
+		orphansSeenAt:  map[string]time.Time{},
+		sidecarTasks:   map[string]bool{},
+		stop:           make(chan struct{}),
+	}
+	if e, ok := f.Storage.(storage.Evicter); ok {
+		e.OnEvict(f.forgetTransform)
+	}
+	if f.TransformCacheMaxBytes > 0 {
+		err := f.clearTransforms()
+		if err != nil {
+			fmt.Printf("Error: %s", err)
+		}
 	}
 	f.ScheduleBackups()
+	f.ScheduleGc()
 	f.ApplyAutomationsToCurrentFiles()
 	return f
 }
 
-func (f *Folder) Upload(name string, bytes []byte) error {
-	var err error
+func (f *Folder) Upload(name string, bytes []byte) (*UploadOutput, error) {
+	hash := hashUpload(bytes)
+	if files := f.findRecentUpload(hash); files != nil {
+		return &UploadOutput{
+			Files:        files,
+			Deduplicated: true,
+		}, nil
+	}
+
+	rawName, original := "", bytes
+	if isRaw(name) {
+		preview, err := exif.Preview(bytes)
+		if err != nil {
+			return nil, err
+		}
+		rawName, bytes = name, preview
+		name = utils.NameWithoutExt(name) + ".jpg"
+	}
+	if automation.IsSvg(bytes) {
+		sanitized, err := automation.SanitizeSvg(bytes)
+		if err != nil {
+			return nil, err
+		}
+		bytes, original = sanitized, sanitized
+	}
 
 	source := automation.Source{
 		SourceConfig: &automation.SourceConfig{
@@ -47,31 +119,93 @@ func (f *Folder) Upload(name string, bytes []byte) error {
 		},
 	}
 
+	var (
+		sinkErr error
+		written []string
+	)
 	sinker := automation.Sinker{
 		SinkerConfig: &automation.SinkerConfig{
 			Sink: func(actx automation.AutomationCtx) {
-				f.Storage.Upload(&storage.UploadInput{
+				if sinkErr != nil {
+					return
+				}
+				err := f.Storage.Upload(&storage.UploadInput{
 					Dir:   f.Dir,
 					Name:  actx.Name,
 					Bytes: actx.Body,
 				})
+				f.invalidateTransforms(actx.Name)
+				if err != nil {
+					sinkErr = err
+					return
+				}
+				written = append(written, actx.Name)
 			},
 		},
 	}
 
+	var files []string
 	for _, a := range f.Automations {
 		actx := automation.AutomationCtx{
 			Name: name,
 			Body: bytes,
 		}
-		_, err = a.Automation.Run(actx, a.Automation.AutomationConfig.Namer, &source, &sinker)
+		outputs, err := a.Automation.Run(actx, a.Automation.AutomationConfig.Namer, &source, &sinker)
 		if err != nil {
 			fmt.Printf("Error: %s", err)
 			continue
 		}
+		files = append(files, outputs...)
+	}
+	if sinkErr != nil {
+		// a partial upload would leave variants without their source
+		for _, file := range written {
+			f.Storage.Delete(&storage.DeleteInput{
+				Dir:  f.Dir,
+				Name: file,
+			})
+			f.invalidateTransforms(file)
+		}
+		return nil, sinkErr
 	}
 
-	return nil
+	var originals []string
+	for _, file := range files {
+		if types.IsSourceFile(&types.File{Name: file}) {
+			// only jpegs and tiffs carry exif
+			_, err := f.storeExif(file, original)
+			if err != nil && !errors.Is(err, exif.ErrUnsupportedFormat) {
+				fmt.Printf("Error: %s", err)
+			}
+			_, err = f.storeDhash(file, bytes)
+			if err != nil {
+				fmt.Printf("Error: %s", err)
+			}
+			_, err = f.storePalette(file, bytes)
+			if err != nil {
+				fmt.Printf("Error: %s", err)
+			}
+
+			if rawName != "" {
+				err = f.Storage.Upload(&storage.UploadInput{
+					Dir:   f.Dir,
+					Name:  originalName(file, rawName),
+					Bytes: original,
+				})
+				if err != nil {
+					return nil, err
+				}
+				originals = append(originals, originalName(file, rawName))
+			}
+		}
+	}
+
+	f.rememberUpload(hash, files)
+
+	return &UploadOutput{
+		Files:     files,
+		Originals: originals,
+	}, nil
 }
 
 func (f *Folder) ReadSize(dir, name string) (*types.Size, error) {
@@ -124,8 +258,72 @@ func (f *Folder) ReadPrefix(prefix string) ([]*types.File, error) {
 	})
 }
 
+// listFiles walks every page of the listing of the files right under dir whose
+// name starts with prefix. ReadAll returns a single page on s3 and lists the
+// whole bucket without a prefix, so it can't be trusted to delete or move a folder.
+func listFiles(s storage.Storage, dir, prefix string) ([]*types.File, error) {
+	dir = utils.JoinPath("/", dir)
+	in := &storage.ListInput{Prefix: strings.TrimSuffix(dir, "/") + "/" + prefix}
+
+	var files []*types.File
+	for {
+		out, err := s.List(in)
+		if err != nil {
+			return nil, err
+		}
+		for _, file := range out.Files {
+			if utils.JoinPath("/", file.Dir) == dir {
+				files = append(files, file)
+			}
+		}
+		if out.NextContinuationToken == "" {
+			return files, nil
+		}
+		in.ContinuationToken = out.NextContinuationToken
+	}
+}
+
 func (f *Folder) Download(name string) ([]byte, error) {
-	return f.Storage.Download(&storage.DonwloadInput{
+	bytes, err := f.Storage.Download(&storage.DonwloadInput{
+		Dir:  f.Dir,
+		Name: name,
+	})
+	if err == nil && (bytes != nil || f.Backup == nil) {
+		return bytes, nil
+	}
+	if f.Backup == nil {
+		f.recordIntegrityEvent(name, err, false)
+		return nil, err
+	}
+
+	backupBytes, backupErr := f.Backup.Download(&storage.DonwloadInput{
+		Dir:  f.Dir,
+		Name: name,
+	})
+	if backupErr != nil || backupBytes == nil {
+		if err != nil {
+			f.recordIntegrityEvent(name, err, false)
+		}
+		return nil, err
+	}
+
+	if err == nil {
+		err = errMissingFromStorage
+	}
+	f.recordIntegrityEvent(name, err, true)
+	return backupBytes, nil
+}
+
+func (f *Folder) PresignedUrl(name string, expiry time.Duration) (string, error) {
+	return f.Storage.PresignedUrl(&storage.PresignedUrlInput{
+		Dir:    f.Dir,
+		Name:   name,
+		Expiry: expiry,
+	})
+}
+
+func (f *Folder) DoesExist(name string) (bool, error) {
+	return f.Storage.DoesExist(&storage.DoesExistInput{
 		Dir:  f.Dir,
 		Name: name,
 	})
@@ -144,10 +342,129 @@ func (f *Folder) DeleteOne(name string) error {
 			Dir:  f.Dir,
 			Name: file.Name,
 		})
+		f.invalidateTransforms(file.Name)
+	}
+	return f.deleteBackup(baseName)
+}
+
+func (f *Folder) DeleteAll() error {
+	err := f.clearTransforms()
+	if err != nil {
+		return err
+	}
+
+	files, err := listFiles(f.Storage, f.Dir, "")
+	if err != nil {
+		return err
+	}
+
+	for _, file := range files {
+		err = f.Storage.Delete(&storage.DeleteInput{
+			Dir:  f.Dir,
+			Name: file.Name,
+		})
+		if err != nil {
+			return err
+		}
+	}
+	return f.deleteBackup("")
+}
+
+// Download falls back on the backup, so deletes must reach it too.
+func (f *Folder) deleteBackup(prefix string) error {
+	if f.Backup == nil {
+		return nil
+	}
+
+	files, err := listFiles(f.Backup, f.Dir, prefix)
+	if err != nil {
+		return err
+	}
+
+	for _, file := range files {
+		err = f.Backup.Delete(&storage.DeleteInput{
+			Dir:  f.Dir,
+			Name: file.Name,
+		})
+		if err != nil {
+			return err
+		}
+	}
+	return nil
+}
+
+func (f *Folder) moveBackup(dir string) error {
+	if f.Backup == nil {
+		return nil
+	}
+
+	files, err := listFiles(f.Backup, f.Dir, "")
+	if err != nil {
+		return err
+	}
+
+	for _, file := range files {
+		err = f.Backup.Rename(&storage.RenameInput{
+			SrcDir:  f.Dir,
+			SrcName: file.Name,
+			DstDir:  dir,
+			DstName: file.Name,
+		})
+		if err != nil {
+			return err
+		}
 	}
 	return nil
 }
 
+func (f *Folder) Move(dir string) error {
+	err := f.clearTransforms()
+	if err != nil {
+		return err
+	}
+
+	files, err := listFiles(f.Storage, f.Dir, "")
+	if err != nil {
+		return err
+	}
+
+	for _, file := range files {
+		err = f.Storage.Rename(&storage.RenameInput{
+			SrcDir:  f.Dir,
+			SrcName: file.Name,
+			DstDir:  dir,
+			DstName: file.Name,
+		})
+		if err != nil {
+			return err
+		}
+	}
+	err = f.moveBackup(dir)
+	if err != nil {
+		return err
+	}
+
+	f.mu.Lock()
+	f.Dir = dir
+	f.mu.Unlock()
+	return nil
+}
+
+func (f *Folder) Close() {
+	f.stopOnce.Do(func() {
+		close(f.stop)
+	})
+}
+
+func (f *Folder) sleep(d time.Duration) bool {
+	select {
+	case <-f.stop:
+		return false
+	case <-time.After(d):
+		return true
+	}
+}
+
 func (f *Folder) ScheduleBackups() {
 	if f.Backup != nil {
 		go func() {
@@ -172,55 +489,66 @@ func (f *Folder) ScheduleBackups() {
 						}
 					}
 				}
-				time.Sleep(60 * time.Second)
+				if !f.sleep(60 * time.Second) {
+					return
+				}
 			}
 		}()
 	}
 }
 
 func (f *Folder) ApplyAutomationsToCurrentFiles() {
-	go func() {
-		files, err := f.ReadAll()
-		if err != nil {
-			return
-		}
-
-		for _, file := range files {
-			if utils.IsValidUUID(utils.NameWithoutExt(file.Name)) {
-				for _, a := range f.Automations {
-					if a.ApplyToCurrentFiles {
-						source := automation.Source{
-							SourceConfig: &automation.SourceConfig{
-								Load: func(Name string) (automation.Body, error) {
-									return f.Download(Name)
-								},
-							},
-						}
+	f.Scheduler.Submit(&scheduler.SubmitInput{
+		Name:     "apply_automations",
+		Group:    f.Dir,
+		Priority: scheduler.PriorityLow,
+		Run:      f.applyAutomationsToCurrentFiles,
+	})
+}
+
+func (f *Folder) applyAutomationsToCurrentFiles(progress scheduler.Progress) error {
+	files, err := f.ReadAll()
+	if err != nil {
+		return err
+	}
 
-						sinker := automation.Sinker{
-							SinkerConfig: &automation.SinkerConfig{
-								Sink: func(actx automation.AutomationCtx) {
-									f.Storage.Upload(&storage.UploadInput{
-										Dir:   f.Dir,
-										Name:  actx.Name,
-										Bytes: actx.Body,
-									})
-								},
+	for n, file := range files {
+		progress(n, len(files))
+		if utils.IsValidUUID(utils.NameWithoutExt(file.Name)) {
+			for _, a := range f.Automations {
+				if a.ApplyToCurrentFiles {
+					source := automation.Source{
+						SourceConfig: &automation.SourceConfig{
+							Load: func(Name string) (automation.Body, error) {
+								return f.Download(Name)
 							},
-						}
+						},
+					}
 
-						actx := automation.AutomationCtx{
-							Name: file.Name,
-						}
-						_, err = a.Automation.Run(actx, nil, &source, &sinker)
-						if err != nil {
-							fmt.Printf("Error: %s", err)
-							continue
-						}
+					sinker := automation.Sinker{
+						SinkerConfig: &automation.SinkerConfig{
+							Sink: func(actx automation.AutomationCtx) {
+								f.Storage.Upload(&storage.UploadInput{
+									Dir:   f.Dir,
+									Name:  actx.Name,
+									Bytes: actx.Body,
+								})
+							},
+						},
+					}
 
+					actx := automation.AutomationCtx{
+						Name: file.Name,
 					}
+					_, err = a.Automation.Run(actx, nil, &source, &sinker)
+					if err != nil {
+						fmt.Printf("Error: %s", err)
+						continue
+					}
+
 				}
 			}
 		}
-	}()
+	}
+	return nil
 }
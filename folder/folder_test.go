@@ -0,0 +1,71 @@
+package folder
+
+import (
+	"fmt"
+	"mindia/storage"
+	"testing"
+)
+
+func newTestFolder(t *testing.T, dir string) *Folder {
+	f := NewFolder(&FolderConfig{
+		Dir: dir,
+		Storage: storage.NewFileSystemStorage(&storage.FilesystemStorageConfig{
+			MountDir: t.TempDir(),
+		}),
+	})
+	t.Cleanup(f.Close)
+	return f
+}
+
+func upload(t *testing.T, s storage.Storage, dir, name string) {
+	t.Helper()
+	err := s.Upload(&storage.UploadInput{Dir: dir, Name: name, Bytes: []byte(name)})
+	if err != nil {
+		t.Fatal(err)
+	}
+}
+
+func countFiles(t *testing.T, s storage.Storage, dir string) int {
+	t.Helper()
+	files, err := listFiles(s, dir, "")
+	if err != nil {
+		t.Fatal(err)
+	}
+	return len(files)
+}
+
+// more files than a single listing page, next to a folder nested in it and one sharing its prefix
+func TestDeleteAllAndMove(t *testing.T) {
+	f := newTestFolder(t, "/houses")
+	for i := 0; i < 1005; i++ {
+		upload(t, f.Storage, "/houses", fmt.Sprintf("%04d.jpg", i))
+	}
+	upload(t, f.Storage, "/houses/garden", "a.jpg")
+	upload(t, f.Storage, "/houses2", "a.jpg")
+
+	if n := countFiles(t, f.Storage, "/houses"); n != 1005 {
+		t.Fatalf("listed %d files, want 1005", n)
+	}
+
+	err := f.Move("/flats")
+	if err != nil {
+		t.Fatal(err)
+	}
+	if n := countFiles(t, f.Storage, "/flats"); n != 1005 {
+		t.Errorf("moved %d files, want 1005", n)
+	}
+	if n := countFiles(t, f.Storage, "/houses"); n != 0 {
+		t.Errorf("%d files left behind", n)
+	}
+
+	err = f.DeleteAll()
+	if err != nil {
+		t.Fatal(err)
+	}
+	if n := countFiles(t, f.Storage, "/flats"); n != 0 {
+		t.Errorf("%d files left after deleting", n)
+	}
+	if countFiles(t, f.Storage, "/houses/garden") != 1 || countFiles(t, f.Storage, "/houses2") != 1 {
+		t.Error("files of other folders were touched")
+	}
+}
@@ -0,0 +1,258 @@
+package folder
+
+import (
+	"mindia/storage"
+	"mindia/types"
+	"mindia/utils"
+	"strings"
+	"time"
+)
+
+const (
+	defaultGcInterval    = time.Hour
+	defaultGcGracePeriod = 24 * time.Hour
+)
+
+var sidecarSuffixes = []string{".exif.json", ".dhash", ".palette.json"}
+
+type GcConfig struct {
+	Interval    time.Duration `yaml:"interval,omitempty"`
+	GracePeriod time.Duration `yaml:"grace_period,omitempty"`
+	DryRun      bool          `yaml:"dry_run,omitempty"`
+}
+
+type GcReport struct {
+	Dir          string    `json:"dir"`
+	DryRun       bool      `json:"dry_run"`
+	Scanned      int       `json:"scanned"`
+	Orphans      []string  `json:"orphans"`
+	Deleted      int       `json:"deleted"`
+	TotalDeleted int       `json:"total_deleted"`
+	RunAt        time.Time `json:"run_at"`
+	Error        string    `json:"error,omitempty"`
+}
+
+func isSidecarOf(name, source string) bool {
+	if !strings.HasPrefix(name, source+".") {
+		return false
+	}
+	if strings.HasPrefix(name, source+".original.") {
+		return true
+	}
+	for _, suffix := range sidecarSuffixes {
+		if name == source+suffix {
+			return true
+		}
+	}
+	return false
+}
+
+func isSidecar(name string, sources []string) bool {
+	for _, source := range sources {
+		if isSidecarOf(name, source) {
+			return true
+		}
+	}
+	return false
+}
+
+// withoutSidecars drops the sidecars of the source files listed in files.
+func withoutSidecars(files []*types.File) []*types.File {
+	var sources []string
+	for _, file := range files {
+		if types.IsSourceFile(file) {
+			sources = append(sources, file.Name)
+		}
+	}
+
+	kept := []*types.File{}
+	for _, file := range files {
+		if !isSidecar(file.Name, sources) {
+			kept = append(kept, file)
+		}
+	}
+	return kept
+}
+
+func (f *Folder) ScheduleGc() {
+	if f.Gc == nil {
+		return
+	}
+	if f.Gc.Interval <= 0 {
+		f.Gc.Interval = defaultGcInterval
+	}
+	if f.Gc.GracePeriod <= 0 {
+		f.Gc.GracePeriod = defaultGcGracePeriod
+	}
+
+	go func() {
+		for f.sleep(f.Gc.Interval) {
+			f.CollectGarbage()
+		}
+	}()
+}
+
+func (f *Folder) GcReport() *GcReport {
+	f.mu.Lock()
+	defer f.mu.Unlock()
+
+	if f.gcReport == nil {
+		return nil
+	}
+	report := *f.gcReport
+	return &report
+}
+
+func (f *Folder) liveFiles() (map[string]bool, []string, error) {
+	files, err := f.ReadAll()
+	if err != nil {
+		return nil, nil, err
+	}
+
+	live := map[string]bool{}
+	var sources []string
+	for _, file := range files {
+		if !types.IsSourceFile(file) {
+			continue
+		}
+		live[file.Name] = true
+		sources = append(sources, file.Name)
+		for _, child := range file.Children {
+			live[child] = true
+		}
+	}
+	return live, sources, nil
+}
+
+func (f *Folder) listFiles() ([]string, error) {
+	dir := utils.JoinPath("/", f.Dir)
+
+	var (
+		names []string
+		token string
+	)
+	for {
+		out, err := f.Storage.List(&storage.ListInput{
+			Prefix:            dir + "/",
+			ContinuationToken: token,
+		})
+		if err != nil {
+			return nil, err
+		}
+		for _, file := range out.Files {
+			if utils.JoinPath("/", file.Dir) == dir {
+				names = append(names, file.Name)
+			}
+		}
+		if out.NextContinuationToken == "" {
+			return names, nil
+		}
+		token = out.NextContinuationToken
+	}
+}
+
+func (f *Folder) CollectGarbage() *GcReport {
+	config := f.Gc
+	if config == nil {
+		config = &GcConfig{
+			GracePeriod: defaultGcGracePeriod,
+			DryRun:      true,
+		}
+	}
+	report := &GcReport{
+		Dir:     f.Dir,
+		DryRun:  config.DryRun,
+		Orphans: []string{},
+		RunAt:   time.Now(),
+	}
+
+	// without automations nothing is derived from the uploads, so nothing is orphaned
+	if len(f.Automations) > 0 {
+		live, sources, err := f.liveFiles()
+		if err == nil {
+			var names []string
+			names, err = f.listFiles()
+			if err == nil {
+				f.sweep(report, config, live, sources, names)
+			}
+		}
+		if err != nil {
+			report.Error = err.Error()
+		}
+	}
+	f.purgeDedupRecords()
+
+	f.mu.Lock()
+	if f.gcReport != nil {
+		report.TotalDeleted = f.gcReport.TotalDeleted
+	}
+	report.TotalDeleted += report.Deleted
+	f.gcReport = report
+	f.mu.Unlock()
+
+	snapshot := *report
+	return &snapshot
+}
+
+// orphanSince returns when the file was last written, so the grace period
+// survives restarts, or when it was first seen orphaned for storages not telling.
+func (f *Folder) orphanSince(name string, now time.Time) time.Time {
+	f.mu.Lock()
+	seenAt, ok := f.orphansSeenAt[name]
+	if !ok {
+		seenAt = now
+		f.orphansSeenAt[name] = now
+	}
+	f.mu.Unlock()
+
+	file, err := f.Storage.ReadOne(&storage.ReadOneInput{
+		Dir:  f.Dir,
+		Name: name,
+	})
+	if err == nil && file != nil && !file.ModTime.IsZero() {
+		return file.ModTime
+	}
+	return seenAt
+}
+
+func (f *Folder) sweep(report *GcReport, config *GcConfig, live map[string]bool, sources []string, names []string) {
+	now := time.Now()
+	orphans := map[string]bool{}
+
+	for _, name := range names {
+		report.Scanned++
+		if live[name] {
+			continue
+		}
+		if isSidecar(name, sources) {
+			continue
+		}
+
+		orphans[name] = true
+		report.Orphans = append(report.Orphans, name)
+
+		if config.DryRun || now.Sub(f.orphanSince(name, now)) < config.GracePeriod {
+			continue
+		}
+		err := f.Storage.Delete(&storage.DeleteInput{
+			Dir:  f.Dir,
+			Name: name,
+		})
+		if err != nil {
+			continue
+		}
+		report.Deleted++
+
+		f.mu.Lock()
+		delete(f.orphansSeenAt, name)
+		f.mu.Unlock()
+	}
+
+	f.mu.Lock()
+	for name := range f.orphansSeenAt {
+		if !orphans[name] {
+			delete(f.orphansSeenAt, name)
+		}
+	}
+	f.mu.Unlock()
+}
@@ -0,0 +1,62 @@
+package folder
+
+import (
+	"mindia/automation"
+	"mindia/storage"
+	"os"
+	"path/filepath"
+	"testing"
+	"time"
+)
+
+const testSource = "0b1c6a4e-2f3d-4c5b-8a9e-1f2d3c4b5a6e.jpg"
+
+func TestGcGracePeriodUsesModTime(t *testing.T) {
+	mountDir := t.TempDir()
+	s := storage.NewFileSystemStorage(&storage.FilesystemStorageConfig{
+		MountDir: mountDir,
+	})
+	f := NewFolder(&FolderConfig{
+		Dir:     "/houses",
+		Storage: s,
+		Automations: []*Automation{
+			{Automation: automation.NewAutomation(&automation.AutomationConfig{})},
+		},
+		Gc: &GcConfig{GracePeriod: time.Hour},
+	})
+	t.Cleanup(f.Close)
+
+	upload(t, s, "/houses", testSource)
+	upload(t, s, "/houses", "old.jpg")
+	upload(t, s, "/houses", "new.jpg")
+	old := time.Now().Add(-2 * time.Hour)
+	err := os.Chtimes(filepath.Join(mountDir, "houses", "old.jpg"), old, old)
+	if err != nil {
+		t.Fatal(err)
+	}
+
+	// a fresh folder has never seen the orphans, only their mod times tell
+	report := f.CollectGarbage()
+	if report.Error != "" || report.Deleted != 1 || len(report.Orphans) != 2 {
+		t.Fatalf("got report %+v, want old.jpg deleted", report)
+	}
+	if n := countFiles(t, s, "/houses"); n != 2 {
+		t.Errorf("%d files left, want the source and new.jpg", n)
+	}
+}
+
+func TestGcSkipsFoldersWithoutAutomations(t *testing.T) {
+	f := newTestFolder(t, "/houses")
+	f.Gc = &GcConfig{GracePeriod: time.Nanosecond}
+	upload(t, f.Storage, "/houses", "a.jpg")
+	upload(t, f.Storage, "/houses", testSource)
+	time.Sleep(time.Millisecond)
+
+	report := f.CollectGarbage()
+	if report.Scanned != 0 || report.Deleted != 0 {
+		t.Errorf("got report %+v, want nothing collected", report)
+	}
+	if n := countFiles(t, f.Storage, "/houses"); n != 2 {
+		t.Errorf("%d files left, want 2", n)
+	}
+}
@@ -0,0 +1,360 @@
+package folder
+
+import (
+	"errors"
+	"fmt"
+	"io"
+	"mindia/breaker"
+	"mindia/scheduler"
+	"mindia/storage"
+	"mindia/utils"
+	"net"
+	"net/http"
+	"net/url"
+	"path"
+	"strings"
+	"syscall"
+	"time"
+
+	"github.com/google/uuid"
+)
+
+const (
+	maxImportSize    = 100 << 20
+	importS3PageSize = 1000
+)
+
+var tooLargeImportError = fmt.Sprintf("file is larger than %d bytes", maxImportSize)
+
+var errBlockedAddress = errors.New("address is not allowed")
+
+// every connection, redirects included, is checked once the host is resolved
+var importHttpClient = &http.Client{
+	Timeout: 60 * time.Second,
+	Transport: &http.Transport{
+		DialContext: (&net.Dialer{
+			Timeout: 30 * time.Second,
+			Control: checkImportAddress,
+		}).DialContext,
+		TLSHandshakeTimeout: 10 * time.Second,
+	},
+	CheckRedirect: func(req *http.Request, via []*http.Request) error {
+		if req.URL.Scheme != "http" && req.URL.Scheme != "https" {
+			return fmt.Errorf("invalid redirect to %s", req.URL)
+		}
+		if len(via) >= 10 {
+			return errors.New("stopped after 10 redirects")
+		}
+		return nil
+	},
+}
+
+var importBreaker = breaker.Register(&breaker.BreakerConfig{
+	Name: "import urls",
+	IsFailure: func(err error) bool {
+		return !errors.Is(err, errBlockedAddress)
+	},
+})
+
+func checkImportAddress(network, address string, _ syscall.RawConn) error {
+	host, _, err := net.SplitHostPort(address)
+	if err != nil {
+		return err
+	}
+	ip := net.ParseIP(host)
+	if ip == nil || !isPublicIp(ip) {
+		return fmt.Errorf("%w: %s", errBlockedAddress, host)
+	}
+	return nil
+}
+
+// isPublicIp rejects loopback, private, link-local (cloud metadata included) and unspecified addresses.
+func isPublicIp(ip net.IP) bool {
+	return !ip.IsLoopback() &&
+		!ip.IsPrivate() &&
+		!ip.IsLinkLocalUnicast() &&
+		!ip.IsLinkLocalMulticast() &&
+		!ip.IsInterfaceLocalMulticast() &&
+		!ip.IsMulticast() &&
+		!ip.IsUnspecified()
+}
+
+type ImportInput struct {
+	Urls      []string           `json:"urls"`
+	S3        *ImportS3Input     `json:"s3"`
+	Priority  scheduler.Priority `json:"priority,omitempty"`
+	DependsOn []string           `json:"depends_on,omitempty"`
+}
+
+type ImportS3Input struct {
+	Bucket          string `json:"bucket"`
+	Region          string `json:"region"`
+	Endpoint        string `json:"endpoint"`
+	AccessKeyId     string `json:"access_key_id"`
+	SecretAccessKey string `json:"secret_access_key"`
+	Prefix          string `json:"prefix"`
+	Provider        string `json:"provider,omitempty"`
+}
+
+type ImportItem struct {
+	Source       string   `json:"source"`
+	Dir          string   `json:"dir"`
+	Files        []string `json:"files,omitempty"`
+	Deduplicated bool     `json:"deduplicated,omitempty"`
+	Error        string   `json:"error,omitempty"`
+}
+
+type Import struct {
+	Id        string        `json:"id"`
+	TaskId    string        `json:"task_id"`
+	Dir       string        `json:"dir"`
+	Status    JobStatus     `json:"status"`
+	Items     []*ImportItem `json:"items"`
+	Error     string        `json:"error,omitempty"`
+	CreatedAt time.Time     `json:"created_at"`
+}
+
+type FolderResolver func(dir string) *Folder
+
+func (f *Folder) Import(in *ImportInput, resolve FolderResolver) *Import {
+	i := &Import{
+		Id:        uuid.New().String(),
+		Dir:       f.Dir,
+		Status:    JobPending,
+		Items:     []*ImportItem{},
+		CreatedAt: time.Now(),
+	}
+
+	f.mu.Lock()
+	var finished []*jobRef
+	for id, previous := range f.imports {
+		if previous.Status == JobDone || previous.Status == JobFailed {
+			finished = append(finished, &jobRef{id: id, createdAt: previous.CreatedAt})
+		}
+	}
+	for _, id := range prunedJobs(finished) {
+		delete(f.imports, id)
+	}
+	f.imports[i.Id] = i
+	f.mu.Unlock()
+
+	task := f.Scheduler.Submit(&scheduler.SubmitInput{
+		Name:      "import",
+		Group:     f.Dir,
+		Priority:  in.Priority,
+		DependsOn: in.DependsOn,
+		Run: func(progress scheduler.Progress) error {
+			return f.runImport(i, in, resolve, progress)
+		},
+	})
+
+	f.mu.Lock()
+	i.TaskId = task.Id
+	f.mu.Unlock()
+
+	return f.ReadImport(i.Id)
+}
+
+func (f *Folder) ReadImport(id string) *Import {
+	f.mu.Lock()
+	defer f.mu.Unlock()
+
+	i, ok := f.imports[id]
+	if !ok {
+		return nil
+	}
+	snapshot := *i
+	snapshot.Items = append([]*ImportItem{}, i.Items...)
+	return &snapshot
+}
+
+func (f *Folder) setImportStatus(i *Import, status JobStatus, err error) {
+	f.mu.Lock()
+	defer f.mu.Unlock()
+
+	i.Status = status
+	if err != nil {
+		i.Error = err.Error()
+	}
+}
+
+func (f *Folder) addImportItem(i *Import, item *ImportItem) {
+	f.mu.Lock()
+	defer f.mu.Unlock()
+
+	i.Items = append(i.Items, item)
+}
+
+func (f *Folder) runImport(i *Import, in *ImportInput, resolve FolderResolver, progress scheduler.Progress) error {
+	f.mu.Lock()
+	i.Items = []*ImportItem{}
+	i.Error = ""
+	f.mu.Unlock()
+	f.setImportStatus(i, JobRunning, nil)
+
+	for n, u := range in.Urls {
+		progress(n, len(in.Urls))
+		f.addImportItem(i, f.importUrl(u))
+	}
+
+	if in.S3 != nil {
+		err := f.importS3(i, in.S3, resolve, func(done, total int) {
+			progress(len(in.Urls)+done, len(in.Urls)+total)
+		})
+		if err != nil {
+			f.setImportStatus(i, JobFailed, err)
+			return err
+		}
+	}
+
+	f.setImportStatus(i, JobDone, nil)
+	return nil
+}
+
+func (f *Folder) importUrl(rawUrl string) *ImportItem {
+	item := &ImportItem{
+		Source: rawUrl,
+		Dir:    f.Dir,
+	}
+
+	u, err := url.Parse(rawUrl)
+	if err != nil || (u.Scheme != "http" && u.Scheme != "https") {
+		item.Error = fmt.Sprintf("invalid url %s", rawUrl)
+		return item
+	}
+
+	var res *http.Response
+	err = importBreaker.Do(func() (err error) {
+		res, err = importHttpClient.Get(rawUrl)
+		if err == nil && res.StatusCode >= 500 {
+			res.Body.Close()
+			return fmt.Errorf("unexpected status %s", res.Status)
+		}
+		return err
+	})
+	if err != nil {
+		item.Error = err.Error()
+		return item
+	}
+	defer res.Body.Close()
+
+	if res.StatusCode >= 400 {
+		item.Error = fmt.Sprintf("unexpected status %s", res.Status)
+		return item
+	}
+
+	body, err := io.ReadAll(io.LimitReader(res.Body, maxImportSize+1))
+	if err != nil {
+		item.Error = err.Error()
+		return item
+	}
+	if len(body) > maxImportSize {
+		item.Error = tooLargeImportError
+		return item
+	}
+
+	f.importBytes(item, path.Base(u.Path), body)
+	return item
+}
+
+func (f *Folder) importS3(i *Import, in *ImportS3Input, resolve FolderResolver, progress scheduler.Progress) error {
+	client, err := storage.NewS3Client(&storage.S3ClientConfig{
+		Bucket:          in.Bucket,
+		Region:          in.Region,
+		Endpoint:        in.Endpoint,
+		AccessKeyId:     in.AccessKeyId,
+		SecretAccessKey: in.SecretAccessKey,
+		Provider:        in.Provider,
+		Unregistered:    true,
+	})
+	if err != nil {
+		return err
+	}
+
+	keys, err := listImportKeys(client, in)
+	if err != nil {
+		return err
+	}
+
+	for n, key := range keys {
+		progress(n, len(keys))
+		if strings.HasSuffix(key, "/") {
+			continue
+		}
+
+		dir := f.Dir
+		if rel := path.Dir(strings.TrimPrefix(strings.TrimPrefix(key, in.Prefix), "/")); rel != "." {
+			dir = utils.JoinPath(f.Dir, rel)
+		}
+
+		item := &ImportItem{
+			Source: fmt.Sprintf("s3://%s/%s", in.Bucket, key),
+			Dir:    dir,
+		}
+
+		target := resolve(dir)
+		if target == nil {
+			item.Error = fmt.Sprintf("no folder configured for %s", dir)
+			f.addImportItem(i, item)
+			continue
+		}
+
+		body, err := client.DownloadObject(&storage.GetObjectParams{
+			Bucket:   in.Bucket,
+			Key:      key,
+			MaxBytes: maxImportSize,
+		})
+		if errors.Is(err, storage.ErrObjectTooLarge) {
+			item.Error = tooLargeImportError
+			f.addImportItem(i, item)
+			continue
+		}
+		if err != nil {
+			item.Error = err.Error()
+			f.addImportItem(i, item)
+			continue
+		}
+
+		target.importBytes(item, path.Base(key), body)
+		f.addImportItem(i, item)
+	}
+
+	return nil
+}
+
+func listImportKeys(client *storage.S3Client, in *ImportS3Input) ([]string, error) {
+	var keys []string
+	startAfter := ""
+	for {
+		page, truncated, err := client.ListObjectsPage(&storage.ListObjectsPageParams{
+			Bucket:     in.Bucket,
+			Prefix:     in.Prefix,
+			StartAfter: startAfter,
+			MaxKeys:    importS3PageSize,
+		})
+		if err != nil {
+			return nil, err
+		}
+		keys = append(keys, page...)
+		if !truncated || len(page) == 0 {
+			return keys, nil
+		}
+		startAfter = page[len(page)-1]
+	}
+}
+
+func (f *Folder) importBytes(item *ImportItem, name string, body []byte) {
+	err := f.checkPolicies(body)
+	if err != nil {
+		item.Error = err.Error()
+		return
+	}
+
+	out, err := f.Upload(name, body)
+	if err != nil {
+		item.Error = err.Error()
+		return
+	}
+	item.Files = out.Files
+	item.Deduplicated = out.Deduplicated
+}
@@ -0,0 +1,70 @@
+package folder
+
+import (
+	"errors"
+	"mindia/policy"
+	"net/http"
+	"net/http/httptest"
+	"strings"
+	"testing"
+)
+
+func TestCheckImportAddress(t *testing.T) {
+	tests := []struct {
+		address string
+		blocked bool
+	}{
+		{"93.184.216.34:443", false},
+		{"[2606:2800:220:1:248:1893:25c8:1946]:443", false},
+		{"127.0.0.1:80", true},
+		{"[::1]:80", true},
+		{"10.0.0.1:80", true},
+		{"172.16.5.4:80", true},
+		{"192.168.1.1:80", true},
+		{"169.254.169.254:80", true},
+		{"[fe80::1]:80", true},
+		{"[fc00::1]:80", true},
+		{"[::ffff:127.0.0.1]:80", true},
+		{"0.0.0.0:80", true},
+	}
+	for _, test := range tests {
+		err := checkImportAddress("tcp", test.address, nil)
+		if blocked := errors.Is(err, errBlockedAddress); blocked != test.blocked {
+			t.Errorf("%s: got error %v, want blocked %t", test.address, err, test.blocked)
+		}
+	}
+}
+
+func TestImportUrlRejectsLocalAddresses(t *testing.T) {
+	hits := 0
+	server := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		hits++
+	}))
+	defer server.Close()
+
+	f := newTestFolder(t, "/houses")
+	item := f.importUrl(server.URL + "/a.jpg")
+	if !strings.Contains(item.Error, errBlockedAddress.Error()) {
+		t.Errorf("got error %q, want the address to be blocked", item.Error)
+	}
+	if hits != 0 {
+		t.Errorf("server was hit %d times", hits)
+	}
+}
+
+func TestImportChecksPolicies(t *testing.T) {
+	f := newTestFolder(t, "/houses")
+	f.Policies = []*policy.Policy{policy.NewPolicy(&policy.PolicyConfig{ContentLengthMax: 4})}
+
+	item := &ImportItem{}
+	f.importBytes(item, "a.txt", []byte("too long"))
+	if !strings.Contains(item.Error, ErrPolicyViolation.Error()) {
+		t.Errorf("got error %q, want a policy violation", item.Error)
+	}
+
+	// policies only gate imports
+	_, err := f.Upload("a.txt", []byte("too long"))
+	if err != nil {
+		t.Errorf("got error %v uploading directly", err)
+	}
+}
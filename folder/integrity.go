@@ -0,0 +1,74 @@
+package folder
+
+import (
+	"errors"
+	"sort"
+	"time"
+)
+
+const maxIntegrityEvents = 1000
+
+var errMissingFromStorage = errors.New("file missing from storage")
+
+type IntegrityEvent struct {
+	Dir       string    `json:"dir"`
+	Name      string    `json:"name"`
+	Error     string    `json:"error"`
+	Recovered bool      `json:"recovered"`
+	At        time.Time `json:"at"`
+}
+
+type IntegrityFailure struct {
+	Dir       string    `json:"dir"`
+	Name      string    `json:"name"`
+	Failures  int       `json:"failures"`
+	Recovered int       `json:"recovered"`
+	LastError string    `json:"last_error"`
+	LastAt    time.Time `json:"last_at"`
+}
+
+func (f *Folder) recordIntegrityEvent(name string, err error, recovered bool) {
+	f.mu.Lock()
+	defer f.mu.Unlock()
+
+	f.integrityEvents = append(f.integrityEvents, &IntegrityEvent{
+		Dir:       f.Dir,
+		Name:      name,
+		Error:     err.Error(),
+		Recovered: recovered,
+		At:        time.Now(),
+	})
+	if len(f.integrityEvents) > maxIntegrityEvents {
+		f.integrityEvents = f.integrityEvents[len(f.integrityEvents)-maxIntegrityEvents:]
+	}
+}
+
+func (f *Folder) IntegrityReport() []*IntegrityFailure {
+	f.mu.Lock()
+	defer f.mu.Unlock()
+
+	byName := map[string]*IntegrityFailure{}
+	var report []*IntegrityFailure
+	for _, e := range f.integrityEvents {
+		failure, ok := byName[e.Name]
+		if !ok {
+			failure = &IntegrityFailure{
+				Dir:  e.Dir,
+				Name: e.Name,
+			}
+			byName[e.Name] = failure
+			report = append(report, failure)
+		}
+		failure.Failures++
+		if e.Recovered {
+			failure.Recovered++
+		}
+		failure.LastError = e.Error
+		failure.LastAt = e.At
+	}
+
+	sort.SliceStable(report, func(i, j int) bool {
+		return report[i].Failures > report[j].Failures
+	})
+	return report
+}
@@ -0,0 +1,39 @@
+package folder
+
+import (
+	"sort"
+	"time"
+)
+
+// Finished jobs kept per folder and kind, the oldest are forgotten first.
+const maxFinishedJobs = 100
+
+type JobStatus string
+
+const (
+	JobPending JobStatus = "pending"
+	JobRunning JobStatus = "running"
+	JobDone    JobStatus = "done"
+	JobFailed  JobStatus = "failed"
+)
+
+type jobRef struct {
+	id        string
+	createdAt time.Time
+}
+
+// prunedJobs returns the ids of the finished jobs to forget.
+func prunedJobs(finished []*jobRef) []string {
+	if len(finished) <= maxFinishedJobs {
+		return nil
+	}
+	sort.Slice(finished, func(i, j int) bool {
+		return finished[i].createdAt.Before(finished[j].createdAt)
+	})
+
+	var ids []string
+	for _, job := range finished[:len(finished)-maxFinishedJobs] {
+		ids = append(ids, job.id)
+	}
+	return ids
+}
@@ -0,0 +1,198 @@
+package folder
+
+import (
+	"encoding/json"
+	"fmt"
+	"image"
+	"image/color"
+	"math"
+	"mindia/automation"
+	"mindia/storage"
+	"mindia/types"
+	"sort"
+
+	"golang.org/x/image/draw"
+)
+
+const (
+	paletteSize     = 5
+	paletteDistance = 32
+)
+
+type Palette struct {
+	Dominant string   `json:"dominant"`
+	Colors   []string `json:"colors"`
+}
+
+type colorBucket struct {
+	r, g, b, count int
+}
+
+func (c *colorBucket) color() color.RGBA {
+	return color.RGBA{
+		R: uint8(c.r / c.count),
+		G: uint8(c.g / c.count),
+		B: uint8(c.b / c.count),
+		A: 0xff,
+	}
+}
+
+func paletteName(name string) string {
+	return name + ".palette.json"
+}
+
+func hexColor(c color.RGBA) string {
+	return fmt.Sprintf("%02x%02x%02x", c.R, c.G, c.B)
+}
+
+func colorDistance(a, b color.RGBA) float64 {
+	dr := float64(a.R) - float64(b.R)
+	dg := float64(a.G) - float64(b.G)
+	db := float64(a.B) - float64(b.B)
+	return math.Sqrt(dr*dr + dg*dg + db*db)
+}
+
+func extractPalette(b []byte) (*Palette, error) {
+	img, _, err := automation.DecodeImage(b)
+	if err != nil {
+		return nil, err
+	}
+
+	small := image.NewRGBA(image.Rect(0, 0, 64, 64))
+	draw.ApproxBiLinear.Scale(small, small.Rect, img, img.Bounds(), draw.Src, nil)
+
+	buckets := map[int]*colorBucket{}
+	for i := 0; i < len(small.Pix); i += 4 {
+		if small.Pix[i+3] < 0x80 {
+			continue
+		}
+		red, green, blue := int(small.Pix[i]), int(small.Pix[i+1]), int(small.Pix[i+2])
+		key := red>>4<<8 | green>>4<<4 | blue>>4
+		bucket, ok := buckets[key]
+		if !ok {
+			bucket = &colorBucket{}
+			buckets[key] = bucket
+		}
+		bucket.r += red
+		bucket.g += green
+		bucket.b += blue
+		bucket.count++
+	}
+
+	sorted := make([]*colorBucket, 0, len(buckets))
+	for _, bucket := range buckets {
+		sorted = append(sorted, bucket)
+	}
+	sort.Slice(sorted, func(i, j int) bool {
+		return sorted[i].count > sorted[j].count
+	})
+
+	var colors []color.RGBA
+	for _, bucket := range sorted {
+		c := bucket.color()
+		distinct := true
+		for _, other := range colors {
+			if colorDistance(c, other) < paletteDistance {
+				distinct = false
+				break
+			}
+		}
+		if distinct {
+			colors = append(colors, c)
+		}
+		if len(colors) == paletteSize {
+			break
+		}
+	}
+
+	p := &Palette{Colors: []string{}}
+	for _, c := range colors {
+		p.Colors = append(p.Colors, hexColor(c))
+	}
+	if len(p.Colors) > 0 {
+		p.Dominant = p.Colors[0]
+	}
+	return p, nil
+}
+
+func (f *Folder) storePalette(name string, b []byte) (*Palette, error) {
+	p, err := extractPalette(b)
+	if err != nil {
+		return nil, err
+	}
+	data, err := json.Marshal(p)
+	if err != nil {
+		return nil, err
+	}
+	err = f.Storage.Upload(&storage.UploadInput{
+		Dir:   f.Dir,
+		Name:  paletteName(name),
+		Bytes: data,
+	})
+	return p, err
+}
+
+func (f *Folder) readStoredPalette(name string) (*Palette, error) {
+	b, err := f.Storage.Download(&storage.DonwloadInput{
+		Dir:  f.Dir,
+		Name: paletteName(name),
+	})
+	if err != nil || b == nil {
+		return nil, err
+	}
+	p := &Palette{}
+	return p, json.Unmarshal(b, p)
+}
+
+func (f *Folder) ReadPalette(name string) (*Palette, error) {
+	p, err := f.readStoredPalette(name)
+	if err != nil || p != nil {
+		return p, err
+	}
+
+	b, err := f.Download(name)
+	if err != nil {
+		return nil, err
+	}
+	if b == nil {
+		return nil, fmt.Errorf("file %s not found", name)
+	}
+	return f.storePalette(name, b)
+}
+
+func (f *Folder) FilterByColor(files []*types.File, hex string, tolerance float64) ([]*types.File, error) {
+	target, err := automation.ParseHexColor(hex)
+	if err != nil {
+		return nil, err
+	}
+	want := color.RGBA{R: target.R, G: target.G, B: target.B, A: 0xff}
+
+	filtered := []*types.File{}
+	var missing []string
+	for _, file := range files {
+		p, err := f.readStoredPalette(file.Name)
+		if err != nil {
+			continue
+		}
+		if p == nil {
+			missing = append(missing, file.Name)
+			continue
+		}
+		for _, h := range p.Colors {
+			c, err := automation.ParseHexColor(h)
+			if err != nil {
+				continue
+			}
+			if colorDistance(want, color.RGBA{R: c.R, G: c.G, B: c.B, A: 0xff}) <= tolerance {
+				filtered = append(filtered, file)
+				break
+			}
+		}
+	}
+
+	f.scheduleSidecars("palettes", missing, func(name string, b []byte) error {
+		_, err := f.storePalette(name, b)
+		return err
+	})
+	return filtered, nil
+}
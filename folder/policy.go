@@ -0,0 +1,31 @@
+package folder
+
+import (
+	"errors"
+	"fmt"
+	"net/http"
+)
+
+var ErrPolicyViolation = errors.New("upload rejected by the folder policies")
+
+func (f *Folder) checkPolicies(body []byte) error {
+	contentType := http.DetectContentType(body)
+	for _, p := range f.Policies {
+		if p.ContentLengthMax > 0 && int64(len(body)) > p.ContentLengthMax {
+			return fmt.Errorf("%w: %d bytes is more than %d", ErrPolicyViolation, len(body), p.ContentLengthMax)
+		}
+		if len(p.ContentTypesAllowed) > 0 && !contains(p.ContentTypesAllowed, contentType) {
+			return fmt.Errorf("%w: content type %s is not allowed", ErrPolicyViolation, contentType)
+		}
+	}
+	return nil
+}
+
+func contains(values []string, value string) bool {
+	for _, v := range values {
+		if v == value {
+			return true
+		}
+	}
+	return false
+}
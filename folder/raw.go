@@ -0,0 +1,21 @@
+package folder
+
+import (
+	"path/filepath"
+	"strings"
+)
+
+var rawExtensions = map[string]bool{
+	".arw": true,
+	".cr2": true,
+	".dng": true,
+	".nef": true,
+}
+
+func isRaw(name string) bool {
+	return rawExtensions[strings.ToLower(filepath.Ext(name))]
+}
+
+func originalName(name, rawName string) string {
+	return name + ".original" + strings.ToLower(filepath.Ext(rawName))
+}
@@ -0,0 +1,50 @@
+package folder
+
+import (
+	"fmt"
+	"mindia/scheduler"
+)
+
+// scheduleSidecars computes the sidecars of kind missing for names in the
+// background, one task per kind at a time, so listing requests never decode images.
+func (f *Folder) scheduleSidecars(kind string, names []string, store func(name string, b []byte) error) {
+	if len(names) == 0 {
+		return
+	}
+	f.mu.Lock()
+	if f.sidecarTasks[kind] {
+		f.mu.Unlock()
+		return
+	}
+	f.sidecarTasks[kind] = true
+	f.mu.Unlock()
+
+	f.Scheduler.Submit(&scheduler.SubmitInput{
+		Name:     "compute_" + kind,
+		Group:    f.Dir,
+		Priority: scheduler.PriorityLow,
+		Run: func(progress scheduler.Progress) error {
+			defer func() {
+				f.mu.Lock()
+				delete(f.sidecarTasks, kind)
+				f.mu.Unlock()
+			}()
+
+			for n, name := range names {
+				progress(n, len(names))
+				b, err := f.Download(name)
+				if err != nil {
+					return err
+				}
+				if b == nil {
+					continue
+				}
+				err = store(name, b)
+				if err != nil {
+					fmt.Printf("Error: %s", err)
+				}
+			}
+			return nil
+		},
+	})
+}
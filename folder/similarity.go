@@ -0,0 +1,131 @@
+package folder
+
+import (
+	"fmt"
+	"image"
+	"math/bits"
+	"mindia/automation"
+	"mindia/storage"
+	"mindia/types"
+	"sort"
+	"strconv"
+	"strings"
+
+	"golang.org/x/image/draw"
+)
+
+type SimilarFile struct {
+	Name     string `json:"name"`
+	Distance int    `json:"distance"`
+}
+
+func dhashName(name string) string {
+	return name + ".dhash"
+}
+
+func dhash(b []byte) (uint64, error) {
+	img, _, err := automation.DecodeImage(b)
+	if err != nil {
+		return 0, err
+	}
+
+	gray := image.NewGray(image.Rect(0, 0, 9, 8))
+	draw.ApproxBiLinear.Scale(gray, gray.Rect, img, img.Bounds(), draw.Src, nil)
+
+	var hash uint64
+	for y := 0; y < 8; y++ {
+		for x := 0; x < 8; x++ {
+			hash <<= 1
+			if gray.GrayAt(x, y).Y < gray.GrayAt(x+1, y).Y {
+				hash |= 1
+			}
+		}
+	}
+	return hash, nil
+}
+
+func (f *Folder) storeDhash(name string, b []byte) (uint64, error) {
+	hash, err := dhash(b)
+	if err != nil {
+		return 0, err
+	}
+	err = f.Storage.Upload(&storage.UploadInput{
+		Dir:   f.Dir,
+		Name:  dhashName(name),
+		Bytes: []byte(fmt.Sprintf("%016x", hash)),
+	})
+	return hash, err
+}
+
+func (f *Folder) readStoredDhash(name string) (uint64, bool, error) {
+	b, err := f.Storage.Download(&storage.DonwloadInput{
+		Dir:  f.Dir,
+		Name: dhashName(name),
+	})
+	if err != nil || b == nil {
+		return 0, false, err
+	}
+	hash, err := strconv.ParseUint(strings.TrimSpace(string(b)), 16, 64)
+	return hash, err == nil, err
+}
+
+func (f *Folder) ReadDhash(name string) (uint64, error) {
+	hash, ok, err := f.readStoredDhash(name)
+	if err != nil || ok {
+		return hash, err
+	}
+
+	b, err := f.Download(name)
+	if err != nil {
+		return 0, err
+	}
+	if b == nil {
+		return 0, fmt.Errorf("file %s not found", name)
+	}
+	return f.storeDhash(name, b)
+}
+
+func (f *Folder) FindSimilar(name string, threshold int) ([]*SimilarFile, error) {
+	hash, err := f.ReadDhash(name)
+	if err != nil {
+		return nil, err
+	}
+
+	files, err := f.ReadAll()
+	if err != nil {
+		return nil, err
+	}
+
+	similar := []*SimilarFile{}
+	var missing []string
+	for _, file := range files {
+		if file.Name == name || !types.IsSourceFile(file) {
+			continue
+		}
+		other, ok, err := f.readStoredDhash(file.Name)
+		if err != nil {
+			continue
+		}
+		if !ok {
+			missing = append(missing, file.Name)
+			continue
+		}
+		distance := bits.OnesCount64(hash ^ other)
+		if distance <= threshold {
+			similar = append(similar, &SimilarFile{
+				Name:     file.Name,
+				Distance: distance,
+			})
+		}
+	}
+
+	f.scheduleSidecars("dhashes", missing, func(name string, b []byte) error {
+		_, err := f.storeDhash(name, b)
+		return err
+	})
+
+	sort.SliceStable(similar, func(i, j int) bool {
+		return similar[i].Distance < similar[j].Distance
+	})
+	return similar, nil
+}
@@ -0,0 +1,176 @@
+package folder
+
+import (
+	"mindia/storage"
+	"mindia/utils"
+	"sort"
+	"time"
+)
+
+const transformCacheDir = storage.TransformCacheDir
+
+type transformCacheEntry struct {
+	name     string
+	size     int64
+	lastUsed time.Time
+}
+
+type TransformFunc func([]byte) ([]byte, error)
+
+func (f *Folder) transformCacheDir() string {
+	return utils.JoinPath(f.Dir, transformCacheDir)
+}
+
+// Transform returns the downloaded file after applying transform to it. ops must
+// describe the transform deterministically since it is part of the cache key.
+func (f *Folder) Transform(name, ops string, transform TransformFunc) ([]byte, error) {
+	source, err := f.Download(name)
+	if err != nil || source == nil {
+		return source, err
+	}
+	if f.TransformCacheMaxBytes <= 0 {
+		return transform(source)
+	}
+
+	// keyed by content so a blob can never outlive the file it was made from
+	key := hashUpload([]byte(hashUpload(source) + "\x00" + ops))
+	if b := f.readCachedTransform(key); b != nil {
+		return b, nil
+	}
+
+	b, err := transform(source)
+	if err != nil || b == nil {
+		return b, err
+	}
+	f.cacheTransform(name, key, b)
+	return b, nil
+}
+
+func (f *Folder) readCachedTransform(key string) []byte {
+	f.mu.Lock()
+	entry, ok := f.transformCache[key]
+	if ok {
+		entry.lastUsed = time.Now()
+	}
+	f.mu.Unlock()
+	if !ok {
+		return nil
+	}
+
+	b, err := f.Storage.Download(&storage.DonwloadInput{
+		Dir:  f.transformCacheDir(),
+		Name: key,
+	})
+	if err != nil || b == nil {
+		f.evictTransforms([]string{key})
+		return nil
+	}
+	return b
+}
+
+func (f *Folder) cacheTransform(name, key string, b []byte) {
+	err := f.Storage.Upload(&storage.UploadInput{
+		Dir:   f.transformCacheDir(),
+		Name:  key,
+		Bytes: b,
+	})
+	if err != nil {
+		return
+	}
+
+	f.mu.Lock()
+	if previous, ok := f.transformCache[key]; ok {
+		f.transformCacheBytes -= previous.size
+	}
+	f.transformCache[key] = &transformCacheEntry{
+		name:     name,
+		size:     int64(len(b)),
+		lastUsed: time.Now(),
+	}
+	f.transformCacheBytes += int64(len(b))
+
+	var keys []string
+	for k := range f.transformCache {
+		keys = append(keys, k)
+	}
+	sort.Slice(keys, func(i, j int) bool {
+		return f.transformCache[keys[i]].lastUsed.Before(f.transformCache[keys[j]].lastUsed)
+	})
+	var evicted []string
+	total := f.transformCacheBytes
+	for _, k := range keys {
+		if total <= f.TransformCacheMaxBytes {
+			break
+		}
+		total -= f.transformCache[k].size
+		evicted = append(evicted, k)
+	}
+	f.mu.Unlock()
+
+	f.evictTransforms(evicted)
+}
+
+func (f *Folder) invalidateTransforms(name string) {
+	f.mu.Lock()
+	var keys []string
+	for k, entry := range f.transformCache {
+		if name == "" || entry.name == name {
+			keys = append(keys, k)
+		}
+	}
+	f.mu.Unlock()
+
+	f.evictTransforms(keys)
+}
+
+func (f *Folder) evictTransforms(keys []string) {
+	for _, key := range keys {
+		f.mu.Lock()
+		if entry, ok := f.transformCache[key]; ok {
+			f.transformCacheBytes -= entry.size
+			delete(f.transformCache, key)
+		}
+		f.mu.Unlock()
+
+		f.Storage.Delete(&storage.DeleteInput{
+			Dir:  f.transformCacheDir(),
+			Name: key,
+		})
+	}
+}
+
+// forgetTransform drops a blob the storage evicted on its own from the index.
+func (f *Folder) forgetTransform(dir, name string) {
+	if utils.JoinPath("/", dir) != utils.JoinPath("/", f.transformCacheDir()) {
+		return
+	}
+	f.mu.Lock()
+	defer f.mu.Unlock()
+
+	if entry, ok := f.transformCache[name]; ok {
+		f.transformCacheBytes -= entry.size
+		delete(f.transformCache, name)
+	}
+}
+
+// clearTransforms deletes every cached blob, including those left by a previous
+// run which the index doesn't know about.
+func (f *Folder) clearTransforms() error {
+	f.invalidateTransforms("")
+
+	files, err := listFiles(f.Storage, f.transformCacheDir(), "")
+	if err != nil {
+		return err
+	}
+
+	for _, file := range files {
+		err = f.Storage.Delete(&storage.DeleteInput{
+			Dir:  f.transformCacheDir(),
+			Name: file.Name,
+		})
+		if err != nil {
+			return err
+		}
+	}
+	return nil
+}
@@ -0,0 +1,48 @@
+package folder
+
+import (
+	"mindia/storage"
+	"testing"
+)
+
+func TestTransformCacheForgetsEvictedBlobs(t *testing.T) {
+	s := storage.NewFileSystemStorage(&storage.FilesystemStorageConfig{
+		MountDir:        t.TempDir(),
+		MaxBytes:        20,
+		EvictTransforms: true,
+	})
+	f := NewFolder(&FolderConfig{
+		Dir:                    "/houses",
+		Storage:                s,
+		TransformCacheMaxBytes: 100,
+	})
+	t.Cleanup(f.Close)
+
+	upload(t, s, "/houses", "a.jpg")
+	transformed := 0
+	transform := func(b []byte) ([]byte, error) {
+		transformed++
+		return []byte("resized!"), nil
+	}
+	_, err := f.Transform("a.jpg", "resize", transform)
+	if err != nil {
+		t.Fatal(err)
+	}
+	if f.transformCacheBytes != 8 {
+		t.Fatalf("%d bytes cached, want 8", f.transformCacheBytes)
+	}
+
+	// no room left for this one without evicting the cached transform
+	err = s.Upload(&storage.UploadInput{Dir: "/houses", Name: "b.jpg", Bytes: make([]byte, 10)})
+	if err != nil {
+		t.Fatal(err)
+	}
+	if len(f.transformCache) != 0 || f.transformCacheBytes != 0 {
+		t.Errorf("index kept %d entries and %d bytes of evicted blobs", len(f.transformCache), f.transformCacheBytes)
+	}
+
+	b, err := f.Transform("a.jpg", "resize", transform)
+	if err != nil || string(b) != "resized!" || transformed != 2 {
+		t.Errorf("got %q and %v after %d transforms", b, err, transformed)
+	}
+}
@@ -0,0 +1,48 @@
+package folder
+
+import (
+	"path"
+	"sort"
+)
+
+type FolderTree struct {
+	Dir       string        `json:"dir"`
+	FileCount int           `json:"file_count"`
+	Children  []*FolderTree `json:"children"`
+}
+
+func BuildTree(folders FoldersMap) *FolderTree {
+	root := &FolderTree{
+		Dir:      "/",
+		Children: []*FolderTree{},
+	}
+	nodes := map[string]*FolderTree{"/": root}
+
+	var dirs []string
+	for dir := range folders {
+		dirs = append(dirs, dir)
+	}
+	sort.Strings(dirs)
+
+	for _, dir := range dirs {
+		node := treeNode(nodes, path.Clean(dir))
+		files, _ := folders[dir].ReadAll()
+		node.FileCount = len(files)
+	}
+
+	return root
+}
+
+func treeNode(nodes map[string]*FolderTree, dir string) *FolderTree {
+	if node, ok := nodes[dir]; ok {
+		return node
+	}
+	parent := treeNode(nodes, path.Dir(dir))
+	node := &FolderTree{
+		Dir:      dir,
+		Children: []*FolderTree{},
+	}
+	parent.Children = append(parent.Children, node)
+	nodes[dir] = node
+	return node
+}
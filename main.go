@@ -1,13 +1,18 @@
 package main
 
 import (
+	"encoding/json"
+	"flag"
+	"fmt"
 	"mindia/apiserver"
 	"mindia/automation"
 	"mindia/automation/namer"
+	"mindia/cli"
 	"mindia/configurer"
 	"mindia/folder"
 	"mindia/policy"
 	"mindia/project"
+	"mindia/scheduler"
 	"mindia/storage"
 	"mindia/types"
 	"mindia/utils"
@@ -27,6 +32,13 @@ func loadEnv() {
 }
 
 func main() {
+	if len(os.Args) > 1 && os.Args[1] == "cli" {
+		os.Exit(cli.Run(os.Args[2:]))
+	}
+
+	selfTest := flag.Bool("self-test", false, "check config, storages and transforms then exit")
+	flag.Parse()
+
 	loadEnv()
 
 	log.Info().Msg("starting mindia")
@@ -56,6 +68,19 @@ func main() {
 		},
 	})
 
+	configurer := configurer.NewConfigurer()
+	schedulerConfig, err := configurer.LoadScheduler()
+	if err != nil {
+		fmt.Printf("Error: %s", err)
+		schedulerConfig = &scheduler.SchedulerConfig{}
+	}
+	folderScheduler := scheduler.NewScheduler(schedulerConfig)
+
+	optimizer := automation.NewOptimizer(&automation.OptimizerConfig{
+		AutomationStepConfig: &automation.AutomationStepConfig{
+			Children: []*automation.Automation{},
+		},
+	})
 	automationXl := automation.NewAutomation(&automation.AutomationConfig{
 		Namer: namer.NewNamerSuffix(&namer.NamerSuffixConfig{
 			Suffix: "xl",
@@ -91,6 +116,7 @@ func main() {
 	automation1 := automation.NewAutomation(&automation.AutomationConfig{
 		Namer: namer.NewNamerUuid(&namer.NamerUuidConfig{}),
 		Steps: []automation.AutomationDoer{
+			optimizer,
 			automation.NewJpegConverter(&automation.JpegConverterConfig{
 				AutomationStepConfig: &automation.AutomationStepConfig{
 					Children: []*automation.Automation{
@@ -120,22 +146,26 @@ func main() {
 		Backup:      filesystemBackupStorage,
 		Automations: automations,
 		Policies:    policies,
+		Scheduler:   folderScheduler,
 	})
 	folder2 := folder.NewFolder(&folder.FolderConfig{
 		Dir:         "/houses/garden",
 		Storage:     filesystemStorage,
 		Automations: automations,
+		Scheduler:   folderScheduler,
 	})
 	folder3 := folder.NewFolder(&folder.FolderConfig{
 		Dir:         "/users",
 		Storage:     s3Storage,
 		Backup:      s3BackupStorage,
 		Automations: automations,
+		Scheduler:   folderScheduler,
 	})
 	folder4 := folder.NewFolder(&folder.FolderConfig{
 		Dir:         "/users/company",
 		Storage:     s3Storage,
 		Automations: automations,
+		Scheduler:   folderScheduler,
 	})
 
 	apiServer := apiserver.NewApiServer(&apiserver.ApiServerConfig{
@@ -145,6 +175,7 @@ func main() {
 	project1 := project.NewProject(&project.ProjectConfig{
 		Name:      "ae",
 		ApiServer: apiServer,
+		Scheduler: folderScheduler,
 		Folders: []*folder.Folder{
 			folder1,
 			folder2,
@@ -153,8 +184,24 @@ func main() {
 		},
 	})
 
-	configurer := configurer.NewConfigurer()
+	if *selfTest {
+		report := project1.SelfTest()
+		b, _ := json.MarshalIndent(report, "", "  ")
+		fmt.Println(string(b))
+		if !report.Ok {
+			os.Exit(1)
+		}
+		return
+	}
+
+	err = configurer.LoadMaintenance(apiServer)
+	if err != nil {
+		fmt.Printf("Error: %s", err)
+	}
 	configurer.PersistConfig(project1)
+	apiServer.OnConfigChange = func() {
+		configurer.PersistConfig(project1)
+	}
 
 	apiServer.Serve()
 }
@@ -1,18 +1,25 @@
 package project
 
 import (
+	"mindia/alert"
 	"mindia/apiserver"
 	"mindia/folder"
+	"mindia/scheduler"
 )
 
 type ProjectConfig struct {
 	Name      string               `yaml:"name"`
 	ApiServer *apiserver.ApiServer `yaml:"api_server"`
 	Folders   []*folder.Folder     `yaml:"folders"`
+	Alerts    *alert.AlertConfig   `yaml:"alerts,omitempty"`
+
+	// Scheduler is persisted so its config is loaded back on the next run.
+	Scheduler *scheduler.Scheduler `yaml:"scheduler,omitempty"`
 }
 
 type Project struct {
 	*ProjectConfig `yaml:",inline"`
+	alerter        *alert.Alerter
 }
 
 func NewProject(config *ProjectConfig) *Project {
@@ -22,5 +29,8 @@ func NewProject(config *ProjectConfig) *Project {
 	for _, f := range p.Folders {
 		p.ApiServer.AddFolder(f)
 	}
+	if p.Alerts != nil {
+		p.alerter = alert.NewAlerter(p.Alerts, p.ApiServer.Folders)
+	}
 	return p
 }
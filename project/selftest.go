@@ -0,0 +1,148 @@
+package project
+
+import (
+	"bytes"
+	"errors"
+	"fmt"
+	"image"
+	"image/jpeg"
+	"mindia/automation"
+	"mindia/storage"
+	"mindia/types"
+
+	"github.com/google/uuid"
+)
+
+type SelfTestCheck struct {
+	Name  string `json:"name"`
+	Ok    bool   `json:"ok"`
+	Error string `json:"error,omitempty"`
+}
+
+type SelfTestReport struct {
+	Ok     bool             `json:"ok"`
+	Checks []*SelfTestCheck `json:"checks"`
+}
+
+func (r *SelfTestReport) add(name string, err error) {
+	check := &SelfTestCheck{
+		Name: name,
+		Ok:   err == nil,
+	}
+	if err != nil {
+		check.Error = err.Error()
+		r.Ok = false
+	}
+	r.Checks = append(r.Checks, check)
+}
+
+func (p *Project) SelfTest() *SelfTestReport {
+	report := &SelfTestReport{Ok: true}
+
+	report.add("config", p.validateConfig())
+	for _, f := range p.Folders {
+		if f.Storage != nil {
+			report.add(fmt.Sprintf("storage %s", f.Dir), checkStorage(f.Storage, f.Dir))
+		}
+		if f.Backup != nil {
+			report.add(fmt.Sprintf("backup %s", f.Dir), checkStorage(f.Backup, f.Dir))
+		}
+	}
+	report.add("transform", checkTransform())
+
+	return report
+}
+
+func (p *Project) validateConfig() error {
+	if p.ApiServer == nil || p.ApiServer.Port <= 0 {
+		return errors.New("api server port is not set")
+	}
+	dirs := map[string]bool{}
+	for _, f := range p.Folders {
+		if f.Dir == "" {
+			return errors.New("folder without dir")
+		}
+		if f.Storage == nil {
+			return fmt.Errorf("folder %s has no storage", f.Dir)
+		}
+		if dirs[f.Dir] {
+			return fmt.Errorf("folder %s is defined twice", f.Dir)
+		}
+		dirs[f.Dir] = true
+	}
+	return nil
+}
+
+func checkStorage(s storage.Storage, dir string) error {
+	name := fmt.Sprintf(".selftest-%s", uuid.New())
+	probe := []byte(name)
+
+	err := s.Upload(&storage.UploadInput{
+		Dir:   dir,
+		Name:  name,
+		Bytes: probe,
+	})
+	if err != nil {
+		return err
+	}
+	defer s.Delete(&storage.DeleteInput{
+		Dir:  dir,
+		Name: name,
+	})
+
+	b, err := s.Download(&storage.DonwloadInput{
+		Dir:  dir,
+		Name: name,
+	})
+	if err != nil {
+		return err
+	}
+	if !bytes.Equal(b, probe) {
+		return errors.New("downloaded probe does not match uploaded probe")
+	}
+	return nil
+}
+
+func checkTransform() error {
+	var in bytes.Buffer
+	err := jpeg.Encode(&in, image.NewRGBA(image.Rect(0, 0, 4, 2)), nil)
+	if err != nil {
+		return err
+	}
+
+	var out []byte
+	a := automation.NewAutomation(&automation.AutomationConfig{
+		Steps: []automation.AutomationDoer{
+			automation.NewResizer(&automation.ResizerConfig{
+				AutomationStepConfig: &automation.AutomationStepConfig{},
+				Size: types.Size{
+					Width:  2,
+					Height: 2,
+				},
+			}),
+		},
+	})
+	sinker := automation.Sinker{
+		SinkerConfig: &automation.SinkerConfig{
+			Sink: func(actx automation.AutomationCtx) {
+				out = actx.Body
+			},
+		},
+	}
+	_, err = a.Run(automation.AutomationCtx{
+		Name: "selftest.jpg",
+		Body: in.Bytes(),
+	}, nil, nil, &sinker)
+	if err != nil {
+		return err
+	}
+
+	img, err := jpeg.DecodeConfig(bytes.NewReader(out))
+	if err != nil {
+		return err
+	}
+	if img.Width != 2 || img.Height != 1 {
+		return fmt.Errorf("resized image is %dx%d, expected 2x1", img.Width, img.Height)
+	}
+	return nil
+}
@@ -0,0 +1,454 @@
+package scheduler
+
+import (
+	"errors"
+	"fmt"
+	"mindia/utils"
+	"sort"
+	"sync"
+	"time"
+
+	"github.com/google/uuid"
+)
+
+const (
+	defaultWorkers       = 4
+	defaultAgingInterval = time.Minute
+	defaultMaxRetries    = 3
+	defaultRetryDelay    = 10 * time.Second
+	maxFinishedTasks     = 1000
+)
+
+var ErrNotDeadLetter = errors.New("task is not in the dead letter queue")
+
+type Priority string
+
+const (
+	PriorityLow    Priority = "low"
+	PriorityNormal Priority = "normal"
+	PriorityHigh   Priority = "high"
+)
+
+func (p Priority) IsValid() bool {
+	return p == "" || p == PriorityLow || p == PriorityNormal || p == PriorityHigh
+}
+
+func (p Priority) weight() int {
+	switch p {
+	case PriorityHigh:
+		return 2
+	case PriorityLow:
+		return 0
+	}
+	return 1
+}
+
+type Status string
+
+const (
+	StatusPending    Status = "pending"
+	StatusRunning    Status = "running"
+	StatusDone       Status = "done"
+	StatusFailed     Status = "failed"
+	StatusDeadLetter Status = "dead_letter"
+)
+
+type Task struct {
+	Id         string    `json:"id"`
+	Name       string    `json:"name"`
+	Group      string    `json:"group,omitempty"`
+	DependsOn  []string  `json:"depends_on,omitempty"`
+	Priority   Priority  `json:"priority"`
+	Status     Status    `json:"status"`
+	Progress   int       `json:"progress"`
+	Attempts   int       `json:"attempts"`
+	MaxRetries int       `json:"max_retries"`
+	Error      string    `json:"error,omitempty"`
+	Errors     []string  `json:"errors,omitempty"`
+	CreatedAt  time.Time `json:"created_at"`
+	StartedAt  time.Time `json:"started_at"`
+	FinishedAt time.Time `json:"finished_at"`
+	queuedAt   time.Time
+	run        func(progress Progress) error
+}
+
+// DependsOn lists the ids of the tasks that must be done first, unknown ids
+// belong to tasks pruned once done.
+type SubmitInput struct {
+	Name       string
+	Group      string
+	Priority   Priority
+	MaxRetries *int
+	DependsOn  []string
+	Run        func(progress Progress) error
+}
+
+// Progress reports that done out of total units of work are finished.
+type Progress func(done, total int)
+
+type SchedulerConfig struct {
+	Workers       int           `yaml:"workers"`
+	AgingInterval time.Duration `yaml:"aging_interval"`
+	MaxRetries    int           `yaml:"max_retries"`
+	RetryDelay    time.Duration `yaml:"retry_delay"`
+
+	// MaxPending makes Saturated report true once this many tasks are waiting.
+	MaxPending int `yaml:"max_pending"`
+
+	// MaxInFlightPerGroup caps the tasks of a same group running at once, so one
+	// busy folder can't take every worker. Folder tasks are grouped by folder dir.
+	MaxInFlightPerGroup int `yaml:"max_in_flight_per_group"`
+
+	// OnDeadLetter is called once a task exhausted its retries.
+	OnDeadLetter func(t *Task) `yaml:"-"`
+}
+
+type Scheduler struct {
+	*SchedulerConfig `yaml:",inline"`
+	tasks            map[string]*Task
+	pending          []*Task
+	running          map[string]int
+	finished         []string
+	stats            map[string]*TaskStats
+	mu               sync.Mutex
+	cond             *sync.Cond
+}
+
+var (
+	defaultScheduler *Scheduler
+	defaultOnce      sync.Once
+)
+
+// Default returns the scheduler shared by the folders that don't get their own.
+func Default() *Scheduler {
+	defaultOnce.Do(func() {
+		defaultScheduler = NewScheduler(&SchedulerConfig{})
+	})
+	return defaultScheduler
+}
+
+func NewScheduler(config *SchedulerConfig) *Scheduler {
+	if config.Workers <= 0 {
+		config.Workers = defaultWorkers
+	}
+	if config.AgingInterval <= 0 {
+		config.AgingInterval = defaultAgingInterval
+	}
+	if config.MaxRetries <= 0 {
+		config.MaxRetries = defaultMaxRetries
+	}
+	if config.RetryDelay <= 0 {
+		config.RetryDelay = defaultRetryDelay
+	}
+	s := &Scheduler{
+		SchedulerConfig: config,
+		tasks:           map[string]*Task{},
+		running:         map[string]int{},
+		stats:           map[string]*TaskStats{},
+	}
+	s.cond = sync.NewCond(&s.mu)
+	for i := 0; i < config.Workers; i++ {
+		go s.work()
+	}
+	return s
+}
+
+func (s *Scheduler) Submit(in *SubmitInput) *Task {
+	priority := in.Priority
+	if priority == "" {
+		priority = PriorityNormal
+	}
+	maxRetries := s.MaxRetries
+	if in.MaxRetries != nil {
+		maxRetries = *in.MaxRetries
+	}
+	t := &Task{
+		Id:         uuid.New().String(),
+		Name:       in.Name,
+		Group:      in.Group,
+		DependsOn:  in.DependsOn,
+		Priority:   priority,
+		Status:     StatusPending,
+		MaxRetries: maxRetries,
+		CreatedAt:  time.Now(),
+		queuedAt:   time.Now(),
+		run:        in.Run,
+	}
+
+	s.mu.Lock()
+	s.tasks[t.Id] = t
+	for _, id := range t.DependsOn {
+		if dep, ok := s.tasks[id]; ok && dep.Status == StatusDeadLetter {
+			t.Status = StatusDeadLetter
+			t.Error = fmt.Sprintf("dependency %s is in the dead letter queue", id)
+			t.FinishedAt = time.Now()
+			break
+		}
+	}
+	if t.Status == StatusPending {
+		s.pending = append(s.pending, t)
+	}
+	submitted := snapshot(t)
+	s.mu.Unlock()
+
+	s.cond.Signal()
+	return submitted
+}
+
+func (s *Scheduler) ReadTask(id string) *Task {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	t, ok := s.tasks[id]
+	if !ok {
+		return nil
+	}
+	return snapshot(t)
+}
+
+func snapshot(t *Task) *Task {
+	copied := *t
+	copied.Errors = append([]string{}, t.Errors...)
+	copied.DependsOn = append([]string{}, t.DependsOn...)
+	return &copied
+}
+
+func (s *Scheduler) ReadTasks() []*Task {
+	return s.filterTasks(func(t *Task) bool {
+		return true
+	})
+}
+
+func (s *Scheduler) ReadDeadLetters() []*Task {
+	return s.filterTasks(func(t *Task) bool {
+		return t.Status == StatusDeadLetter
+	})
+}
+
+func (s *Scheduler) filterTasks(keep func(t *Task) bool) []*Task {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	tasks := []*Task{}
+	for _, t := range s.tasks {
+		if keep(t) {
+			tasks = append(tasks, snapshot(t))
+		}
+	}
+	sort.Slice(tasks, func(i, j int) bool {
+		return tasks[i].CreatedAt.Before(tasks[j].CreatedAt)
+	})
+	return tasks
+}
+
+// A pending task gains one priority level for every AgingInterval it waited,
+// so bulk work still runs while urgent tasks keep coming in.
+func (s *Scheduler) score(t *Task, now time.Time) int {
+	return t.Priority.weight() + int(now.Sub(t.CreatedAt)/s.AgingInterval)
+}
+
+func (s *Scheduler) dependenciesDone(t *Task) bool {
+	for _, id := range t.DependsOn {
+		if dep, ok := s.tasks[id]; ok && dep.Status != StatusDone {
+			return false
+		}
+	}
+	return true
+}
+
+// failDependents moves the pending tasks depending on a dead letter to the
+// dead letter queue as well, since they can't run anymore.
+func (s *Scheduler) failDependents(id string) []*Task {
+	var failed []*Task
+	pending := s.pending[:0]
+	for _, t := range s.pending {
+		if !dependsOn(t, id) {
+			pending = append(pending, t)
+			continue
+		}
+		t.Status = StatusDeadLetter
+		t.Error = fmt.Sprintf("dependency %s moved to the dead letter queue", id)
+		t.FinishedAt = time.Now()
+		failed = append(failed, t)
+	}
+	s.pending = pending
+
+	for _, t := range failed {
+		failed = append(failed, s.failDependents(t.Id)...)
+	}
+	return failed
+}
+
+func dependsOn(t *Task, id string) bool {
+	for _, dep := range t.DependsOn {
+		if dep == id {
+			return true
+		}
+	}
+	return false
+}
+
+func (s *Scheduler) available(group string) bool {
+	return group == "" || s.MaxInFlightPerGroup <= 0 || s.running[group] < s.MaxInFlightPerGroup
+}
+
+// pick returns the index of the pending task to run next, or -1 when every
+// pending task belongs to a group already running its share. On equal scores
+// the group with the fewest running tasks goes first.
+func (s *Scheduler) pick(now time.Time) int {
+	best := -1
+	for i, t := range s.pending {
+		if !s.available(t.Group) || !s.dependenciesDone(t) {
+			continue
+		}
+		if best < 0 {
+			best = i
+			continue
+		}
+		score, bestScore := s.score(t, now), s.score(s.pending[best], now)
+		if score > bestScore || (score == bestScore && s.running[t.Group] < s.running[s.pending[best].Group]) {
+			best = i
+		}
+	}
+	return best
+}
+
+func (s *Scheduler) claim() *Task {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	best := s.pick(time.Now())
+	for best < 0 {
+		s.cond.Wait()
+		best = s.pick(time.Now())
+	}
+
+	t := s.pending[best]
+	s.pending = append(s.pending[:best], s.pending[best+1:]...)
+	s.running[t.Group]++
+	t.Status = StatusRunning
+	t.StartedAt = time.Now()
+	s.taskStats(t.Name).ClaimLatency.observe(t.StartedAt.Sub(t.queuedAt))
+	t.Progress = 0
+	t.Attempts++
+	return t
+}
+
+func (s *Scheduler) enqueue(t *Task) {
+	s.mu.Lock()
+	t.Status = StatusPending
+	t.queuedAt = time.Now()
+	s.pending = append(s.pending, t)
+	s.mu.Unlock()
+
+	s.cond.Signal()
+}
+
+func (s *Scheduler) Requeue(id string) (*Task, error) {
+	s.mu.Lock()
+	t, ok := s.tasks[id]
+	if !ok || t.Status != StatusDeadLetter {
+		s.mu.Unlock()
+		return nil, ErrNotDeadLetter
+	}
+	s.requeue(t)
+	requeued := snapshot(t)
+	s.mu.Unlock()
+
+	s.cond.Broadcast()
+	return requeued, nil
+}
+
+// requeue brings back the dead lettered dependencies along with the task,
+// otherwise it would wait on them forever.
+func (s *Scheduler) requeue(t *Task) {
+	t.Attempts = 0
+	t.Error = ""
+	t.Status = StatusPending
+	t.queuedAt = time.Now()
+	s.pending = append(s.pending, t)
+	for _, id := range t.DependsOn {
+		if dep, ok := s.tasks[id]; ok && dep.Status == StatusDeadLetter {
+			s.requeue(dep)
+		}
+	}
+}
+
+func (s *Scheduler) work() {
+	for {
+		t := s.claim()
+		err := t.run(func(done, total int) {
+			s.setProgress(t, done, total)
+		})
+		s.finish(t, err)
+	}
+}
+
+func (s *Scheduler) setProgress(t *Task, done, total int) {
+	if total <= 0 {
+		return
+	}
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	t.Progress = utils.MinInt(100, utils.MaxInt(0, done*100/total))
+}
+
+// finish wakes every worker once the task is settled, since it can free a group
+// slot and unblock the tasks depending on it.
+func (s *Scheduler) finish(t *Task, err error) {
+	defer s.cond.Broadcast()
+
+	s.mu.Lock()
+	s.running[t.Group]--
+	t.FinishedAt = time.Now()
+	stats := s.taskStats(t.Name)
+	stats.Duration.observe(t.FinishedAt.Sub(t.StartedAt))
+	if err == nil {
+		stats.Done++
+		t.Status = StatusDone
+		t.Progress = 100
+		t.Error = ""
+		s.prune(t)
+		s.mu.Unlock()
+		return
+	}
+
+	t.Error = err.Error()
+	t.Errors = append(t.Errors, fmt.Sprintf("attempt %d: %s", t.Attempts, err))
+	if t.Attempts <= t.MaxRetries {
+		stats.Retries++
+		t.Status = StatusFailed
+		s.mu.Unlock()
+		time.AfterFunc(time.Duration(t.Attempts)*s.RetryDelay, func() {
+			s.enqueue(t)
+		})
+		return
+	}
+
+	t.Status = StatusDeadLetter
+	deadLetters := []*Task{snapshot(t)}
+	for _, dependent := range s.failDependents(t.Id) {
+		deadLetters = append(deadLetters, snapshot(dependent))
+	}
+	for _, deadLetter := range deadLetters {
+		s.taskStats(deadLetter.Name).DeadLetters++
+	}
+	s.mu.Unlock()
+
+	for _, deadLetter := range deadLetters {
+		fmt.Printf("Error: task %s (%s) moved to the dead letter queue: %s", deadLetter.Id, deadLetter.Name, deadLetter.Error)
+		if s.OnDeadLetter != nil {
+			s.OnDeadLetter(deadLetter)
+		}
+	}
+}
+
+// Dead letters stay until they are requeued, only done tasks are pruned.
+func (s *Scheduler) prune(t *Task) {
+	s.finished = append(s.finished, t.Id)
+	if len(s.finished) > maxFinishedTasks {
+		delete(s.tasks, s.finished[0])
+		s.finished = s.finished[1:]
+	}
+}
@@ -0,0 +1,185 @@
+package scheduler
+
+import (
+	"errors"
+	"sync/atomic"
+	"testing"
+	"time"
+)
+
+func newTestScheduler() *Scheduler {
+	return NewScheduler(&SchedulerConfig{
+		Workers:    2,
+		MaxRetries: 2,
+		RetryDelay: time.Millisecond,
+	})
+}
+
+// waitFor polls the task until it settles in status, failing the test after a second.
+func waitFor(t *testing.T, s *Scheduler, id string, status Status) *Task {
+	t.Helper()
+	deadline := time.Now().Add(time.Second)
+	for {
+		task := s.ReadTask(id)
+		if task != nil && task.Status == status {
+			return task
+		}
+		if time.Now().After(deadline) {
+			t.Fatalf("task %s never reached %s, last seen %+v", id, status, task)
+		}
+		time.Sleep(time.Millisecond)
+	}
+}
+
+// failing returns a run failing its first n attempts.
+func failing(n int32) func(progress Progress) error {
+	var attempts int32
+	return func(progress Progress) error {
+		if atomic.AddInt32(&attempts, 1) <= n {
+			return errors.New("boom")
+		}
+		return nil
+	}
+}
+
+func TestRetries(t *testing.T) {
+	zero := 0
+	tests := []struct {
+		name       string
+		failures   int32
+		maxRetries *int
+		status     Status
+		attempts   int
+	}{
+		{"success", 0, nil, StatusDone, 1},
+		{"retried", 2, nil, StatusDone, 3},
+		{"exhausted", 3, nil, StatusDeadLetter, 3},
+		{"no retries", 1, &zero, StatusDeadLetter, 1},
+	}
+	for _, test := range tests {
+		s := newTestScheduler()
+		task := s.Submit(&SubmitInput{
+			Name:       test.name,
+			MaxRetries: test.maxRetries,
+			Run:        failing(test.failures),
+		})
+		done := waitFor(t, s, task.Id, test.status)
+		if done.Attempts != test.attempts {
+			t.Errorf("%s: got %d attempts, want %d", test.name, done.Attempts, test.attempts)
+		}
+		if test.status == StatusDeadLetter && len(s.ReadDeadLetters()) != 1 {
+			t.Errorf("%s: expected a single dead letter", test.name)
+		}
+	}
+}
+
+func TestRequeue(t *testing.T) {
+	s := newTestScheduler()
+	zero := 0
+	task := s.Submit(&SubmitInput{
+		Name:       "requeued",
+		MaxRetries: &zero,
+		Run:        failing(1),
+	})
+	waitFor(t, s, task.Id, StatusDeadLetter)
+
+	_, err := s.Requeue(task.Id)
+	if err != nil {
+		t.Fatal(err)
+	}
+	waitFor(t, s, task.Id, StatusDone)
+
+	_, err = s.Requeue(task.Id)
+	if !errors.Is(err, ErrNotDeadLetter) {
+		t.Errorf("got error %v, want %v", err, ErrNotDeadLetter)
+	}
+}
+
+func TestRequeueDependencies(t *testing.T) {
+	s := newTestScheduler()
+	zero := 0
+	first := s.Submit(&SubmitInput{
+		Name:       "first",
+		MaxRetries: &zero,
+		Run:        failing(1),
+	})
+	second := s.Submit(&SubmitInput{
+		Name:      "second",
+		DependsOn: []string{first.Id},
+		Run:       failing(0),
+	})
+	waitFor(t, s, second.Id, StatusDeadLetter)
+
+	_, err := s.Requeue(second.Id)
+	if err != nil {
+		t.Fatal(err)
+	}
+	waitFor(t, s, first.Id, StatusDone)
+	waitFor(t, s, second.Id, StatusDone)
+}
+
+func TestDependencies(t *testing.T) {
+	s := newTestScheduler()
+	release := make(chan struct{})
+	first := s.Submit(&SubmitInput{
+		Name: "first",
+		Run: func(progress Progress) error {
+			<-release
+			return nil
+		},
+	})
+	second := s.Submit(&SubmitInput{
+		Name:      "second",
+		DependsOn: []string{first.Id},
+		Run:       failing(0),
+	})
+
+	waitFor(t, s, first.Id, StatusRunning)
+	time.Sleep(10 * time.Millisecond)
+	if status := s.ReadTask(second.Id).Status; status != StatusPending {
+		t.Fatalf("dependent task ran before its dependency, got %s", status)
+	}
+	close(release)
+	waitFor(t, s, second.Id, StatusDone)
+}
+
+func TestDeadLetterDependencies(t *testing.T) {
+	s := newTestScheduler()
+	zero := 0
+	release := make(chan struct{})
+	first := s.Submit(&SubmitInput{
+		Name:       "first",
+		MaxRetries: &zero,
+		Run: func(progress Progress) error {
+			<-release
+			return errors.New("boom")
+		},
+	})
+	second := s.Submit(&SubmitInput{
+		Name:      "second",
+		DependsOn: []string{first.Id},
+		Run:       failing(0),
+	})
+	third := s.Submit(&SubmitInput{
+		Name:      "third",
+		DependsOn: []string{second.Id},
+		Run:       failing(0),
+	})
+
+	close(release)
+	waitFor(t, s, first.Id, StatusDeadLetter)
+	waitFor(t, s, second.Id, StatusDeadLetter)
+	waitFor(t, s, third.Id, StatusDeadLetter)
+
+	late := s.Submit(&SubmitInput{
+		Name:      "late",
+		DependsOn: []string{first.Id},
+		Run:       failing(0),
+	})
+	if late.Status != StatusDeadLetter {
+		t.Errorf("task submitted after its dependency failed got %s", late.Status)
+	}
+	if attempts := s.ReadTask(third.Id).Attempts; attempts != 0 {
+		t.Errorf("dependent of a dead letter ran %d times", attempts)
+	}
+}
@@ -0,0 +1,141 @@
+package scheduler
+
+import (
+	"sort"
+	"time"
+)
+
+var histogramBounds = []time.Duration{
+	100 * time.Millisecond,
+	time.Second,
+	10 * time.Second,
+	time.Minute,
+	10 * time.Minute,
+	time.Hour,
+}
+
+// Buckets are cumulative: each bound counts the observations lower or equal to it.
+type Histogram struct {
+	Count      int            `json:"count"`
+	SumSeconds float64        `json:"sum_seconds"`
+	Buckets    map[string]int `json:"buckets"`
+}
+
+type TaskStats struct {
+	Name         string     `json:"name"`
+	Pending      int        `json:"pending"`
+	Running      int        `json:"running"`
+	Done         int        `json:"done"`
+	Retries      int        `json:"retries"`
+	DeadLetters  int        `json:"dead_letters"`
+	ClaimLatency *Histogram `json:"claim_latency"`
+	Duration     *Histogram `json:"duration"`
+}
+
+type SchedulerStats struct {
+	Workers    int          `json:"workers"`
+	Pending    int          `json:"pending"`
+	Running    int          `json:"running"`
+	MaxPending int          `json:"max_pending,omitempty"`
+	Saturated  bool         `json:"saturated"`
+	Tasks      []*TaskStats `json:"tasks"`
+}
+
+func newHistogram() *Histogram {
+	h := &Histogram{
+		Buckets: map[string]int{"+Inf": 0},
+	}
+	for _, bound := range histogramBounds {
+		h.Buckets[bound.String()] = 0
+	}
+	return h
+}
+
+func (h *Histogram) observe(d time.Duration) {
+	h.Count++
+	h.SumSeconds += d.Seconds()
+	for _, bound := range histogramBounds {
+		if d <= bound {
+			h.Buckets[bound.String()]++
+		}
+	}
+	h.Buckets["+Inf"]++
+}
+
+func (h *Histogram) copy() *Histogram {
+	copied := *h
+	copied.Buckets = map[string]int{}
+	for k, v := range h.Buckets {
+		copied.Buckets[k] = v
+	}
+	return &copied
+}
+
+func (s *Scheduler) taskStats(name string) *TaskStats {
+	stats, ok := s.stats[name]
+	if !ok {
+		stats = &TaskStats{
+			Name:         name,
+			ClaimLatency: newHistogram(),
+			Duration:     newHistogram(),
+		}
+		s.stats[name] = stats
+	}
+	return stats
+}
+
+// Saturated tells when MaxPending tasks are waiting, callers are expected to
+// push back on new work until it drains.
+func (s *Scheduler) Saturated() bool {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	return s.MaxPending > 0 && len(s.pending) >= s.MaxPending
+}
+
+func (s *Scheduler) Stats() *SchedulerStats {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	tasks := map[string]*TaskStats{}
+	for name, stats := range s.stats {
+		copied := *stats
+		copied.ClaimLatency = stats.ClaimLatency.copy()
+		copied.Duration = stats.Duration.copy()
+		tasks[name] = &copied
+	}
+	get := func(name string) *TaskStats {
+		if _, ok := tasks[name]; !ok {
+			tasks[name] = &TaskStats{
+				Name:         name,
+				ClaimLatency: newHistogram(),
+				Duration:     newHistogram(),
+			}
+		}
+		return tasks[name]
+	}
+
+	out := &SchedulerStats{
+		Workers:    s.Workers,
+		Pending:    len(s.pending),
+		MaxPending: s.MaxPending,
+		Saturated:  s.MaxPending > 0 && len(s.pending) >= s.MaxPending,
+		Tasks:      []*TaskStats{},
+	}
+	for _, t := range s.pending {
+		get(t.Name).Pending++
+	}
+	for _, t := range s.tasks {
+		if t.Status == StatusRunning {
+			get(t.Name).Running++
+			out.Running++
+		}
+	}
+	for _, stats := range tasks {
+		out.Tasks = append(out.Tasks, stats)
+	}
+	sort.Slice(out.Tasks, func(i, j int) bool {
+		return out.Tasks[i].Name < out.Tasks[j].Name
+	})
+	return out
+}
@@ -0,0 +1,244 @@
+package storage
+
+import (
+	"errors"
+	"io/fs"
+	"os"
+	"path"
+	"path/filepath"
+	"sort"
+	"strings"
+	"sync"
+	"time"
+)
+
+// Files the folders keep under this dir are derived and can be regenerated.
+const TransformCacheDir = ".transforms"
+
+var ErrQuotaExceeded = errors.New("filesystem storage quota exceeded")
+
+// Mount dirs of every filesystem storage, so one mounted inside another isn't
+// counted against the outer quota.
+var (
+	mountDirs   = map[string]bool{}
+	mountDirsMu sync.Mutex
+)
+
+type FilesystemUsage struct {
+	MountDir  string `json:"mount_dir"`
+	UsedBytes int64  `json:"used_bytes"`
+	MaxBytes  int64  `json:"max_bytes,omitempty"`
+	FreeBytes uint64 `json:"free_bytes"`
+}
+
+// An Evicter deletes transforms on its own to make room, callers indexing them
+// are told through OnEvict.
+type Evicter interface {
+	OnEvict(fn func(dir, name string))
+}
+
+type artifact struct {
+	path    string
+	size    int64
+	modTime time.Time
+}
+
+func (s *FilesystemStorage) Usage() (*FilesystemUsage, error) {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	err := s.loadUsage()
+	if err != nil {
+		return nil, err
+	}
+	free, err := freeBytes(s.MountDir)
+	if err != nil {
+		return nil, err
+	}
+	return &FilesystemUsage{
+		MountDir:  s.MountDir,
+		UsedBytes: s.used,
+		MaxBytes:  s.MaxBytes,
+		FreeBytes: free,
+	}, nil
+}
+
+func registerMountDir(dir string) {
+	mountDirsMu.Lock()
+	defer mountDirsMu.Unlock()
+
+	mountDirs[absPath(dir)] = true
+}
+
+func isMountDir(dir string) bool {
+	mountDirsMu.Lock()
+	defer mountDirsMu.Unlock()
+
+	return mountDirs[absPath(dir)]
+}
+
+func absPath(p string) string {
+	abs, err := filepath.Abs(p)
+	if err != nil {
+		return filepath.Clean(p)
+	}
+	return abs
+}
+
+func (s *FilesystemStorage) walkFiles(fn func(p string, info fs.FileInfo)) error {
+	root := absPath(s.MountDir)
+	err := filepath.WalkDir(s.MountDir, func(p string, d fs.DirEntry, err error) error {
+		if err != nil {
+			return err
+		}
+		if d.IsDir() {
+			if absPath(p) != root && isMountDir(p) {
+				return filepath.SkipDir
+			}
+			return nil
+		}
+		info, err := d.Info()
+		if err != nil {
+			return nil
+		}
+		fn(p, info)
+		return nil
+	})
+	if errors.Is(err, os.ErrNotExist) {
+		return nil
+	}
+	return err
+}
+
+func (s *FilesystemStorage) loadUsage() error {
+	if s.usageLoaded {
+		return nil
+	}
+	var used int64
+	err := s.walkFiles(func(p string, info fs.FileInfo) {
+		used += info.Size()
+	})
+	if err != nil {
+		return err
+	}
+	s.used = used
+	s.usageLoaded = true
+	return nil
+}
+
+func fileSize(p string) int64 {
+	info, err := os.Stat(p)
+	if err != nil {
+		return 0
+	}
+	return info.Size()
+}
+
+func (s *FilesystemStorage) OnEvict(fn func(dir, name string)) {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	s.onEvict = append(s.onEvict, fn)
+}
+
+// reserve accounts for size bytes about to be written at p, replacing
+// whatever is there, and fails when it doesn't fit in MaxBytes.
+func (s *FilesystemStorage) reserve(p string, size int64) error {
+	if s.MaxBytes <= 0 {
+		return nil
+	}
+	s.mu.Lock()
+	evicted, err := s.reserveLocked(p, size)
+	onEvict := s.onEvict
+	s.mu.Unlock()
+
+	// called unlocked so the callbacks may use the storage
+	for _, e := range evicted {
+		rel, relErr := filepath.Rel(s.MountDir, e)
+		if relErr != nil {
+			continue
+		}
+		rel = filepath.ToSlash(rel)
+		for _, fn := range onEvict {
+			fn("/"+path.Dir(rel), path.Base(rel))
+		}
+	}
+	return err
+}
+
+func (s *FilesystemStorage) reserveLocked(p string, size int64) ([]string, error) {
+	err := s.loadUsage()
+	if err != nil {
+		return nil, err
+	}
+	var evicted []string
+	delta := size - fileSize(p)
+	if s.used+delta > s.MaxBytes && s.EvictTransforms {
+		evicted = s.evictArtifacts(s.used+delta-s.MaxBytes, p)
+	}
+	if s.used+delta > s.MaxBytes {
+		return evicted, ErrQuotaExceeded
+	}
+	s.used += delta
+	return evicted, nil
+}
+
+func (s *FilesystemStorage) release(p string) {
+	if s.MaxBytes <= 0 {
+		return
+	}
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	if s.usageLoaded {
+		s.used -= fileSize(p)
+	}
+}
+
+// resetUsage makes the next write walk the mount dir again, used after a
+// failed write left the counter out of sync with the disk.
+func (s *FilesystemStorage) resetUsage() {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	s.usageLoaded = false
+}
+
+func isArtifact(p string) bool {
+	return strings.Contains(filepath.ToSlash(p), "/"+TransformCacheDir+"/")
+}
+
+// evictArtifacts removes the oldest transforms until need bytes are freed and
+// returns their paths.
+func (s *FilesystemStorage) evictArtifacts(need int64, keep string) []string {
+	keep = filepath.Clean(keep)
+	var artifacts []*artifact
+	s.walkFiles(func(p string, info fs.FileInfo) {
+		if filepath.Clean(p) != keep && isArtifact(p) {
+			artifacts = append(artifacts, &artifact{
+				path:    p,
+				size:    info.Size(),
+				modTime: info.ModTime(),
+			})
+		}
+	})
+	sort.Slice(artifacts, func(i, j int) bool {
+		return artifacts[i].modTime.Before(artifacts[j].modTime)
+	})
+
+	var (
+		freed   int64
+		evicted []string
+	)
+	for _, a := range artifacts {
+		if freed >= need {
+			break
+		}
+		if os.Remove(a.path) == nil {
+			freed += a.size
+			s.used -= a.size
+			evicted = append(evicted, a.path)
+		}
+	}
+	return evicted
+}
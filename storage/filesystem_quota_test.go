@@ -0,0 +1,126 @@
+package storage
+
+import (
+	"errors"
+	"path/filepath"
+	"testing"
+)
+
+func usedBytes(t *testing.T, s *FilesystemStorage) int64 {
+	t.Helper()
+	usage, err := s.Usage()
+	if err != nil {
+		t.Fatal(err)
+	}
+	return usage.UsedBytes
+}
+
+func TestFilesystemQuota(t *testing.T) {
+	upload := func(dir, name string, size int) func(s *FilesystemStorage) error {
+		return func(s *FilesystemStorage) error {
+			return s.Upload(&UploadInput{Dir: dir, Name: name, Bytes: make([]byte, size)})
+		}
+	}
+	remove := func(dir, name string) func(s *FilesystemStorage) error {
+		return func(s *FilesystemStorage) error {
+			return s.Delete(&DeleteInput{Dir: dir, Name: name})
+		}
+	}
+
+	tests := []struct {
+		name  string
+		evict bool
+		steps []func(s *FilesystemStorage) error
+		err   error
+		used  int64
+	}{
+		{
+			name:  "under the quota",
+			steps: []func(s *FilesystemStorage) error{upload("/f", "a", 4), upload("/f", "b", 6)},
+			used:  10,
+		},
+		{
+			name:  "over the quota",
+			steps: []func(s *FilesystemStorage) error{upload("/f", "a", 6), upload("/f", "b", 6)},
+			err:   ErrQuotaExceeded,
+			used:  6,
+		},
+		{
+			name:  "overwrite counts the difference",
+			steps: []func(s *FilesystemStorage) error{upload("/f", "a", 6), upload("/f", "a", 9)},
+			used:  9,
+		},
+		{
+			name:  "delete releases",
+			steps: []func(s *FilesystemStorage) error{upload("/f", "a", 6), remove("/f", "a"), upload("/f", "b", 8)},
+			used:  8,
+		},
+		{
+			name:  "transforms are kept without eviction",
+			steps: []func(s *FilesystemStorage) error{upload("/f/"+TransformCacheDir, "t", 6), upload("/f", "a", 6)},
+			err:   ErrQuotaExceeded,
+			used:  6,
+		},
+		{
+			name:  "transforms are evicted first",
+			evict: true,
+			steps: []func(s *FilesystemStorage) error{upload("/f/"+TransformCacheDir, "t", 6), upload("/f", "a", 6)},
+			used:  6,
+		},
+		{
+			name:  "files are never evicted",
+			evict: true,
+			steps: []func(s *FilesystemStorage) error{upload("/f", "a", 6), upload("/f", "b", 6)},
+			err:   ErrQuotaExceeded,
+			used:  6,
+		},
+	}
+	for _, test := range tests {
+		s := NewFileSystemStorage(&FilesystemStorageConfig{
+			MountDir:        t.TempDir(),
+			MaxBytes:        10,
+			EvictTransforms: test.evict,
+		})
+		var err error
+		for _, step := range test.steps {
+			if err = step(s); err != nil {
+				break
+			}
+		}
+		if !errors.Is(err, test.err) {
+			t.Errorf("%s: got error %v, want %v", test.name, err, test.err)
+		}
+		if used := usedBytes(t, s); used != test.used {
+			t.Errorf("%s: %d bytes used, want %d", test.name, used, test.used)
+		}
+
+		// the counter must match a fresh walk of the mount dir
+		s.resetUsage()
+		if used := usedBytes(t, s); used != test.used {
+			t.Errorf("%s: %d bytes on disk, want %d", test.name, used, test.used)
+		}
+	}
+}
+
+func TestFilesystemQuotaSkipsNestedMounts(t *testing.T) {
+	dir := t.TempDir()
+	s := NewFileSystemStorage(&FilesystemStorageConfig{
+		MountDir: dir,
+		MaxBytes: 10,
+	})
+	backup := NewFileSystemStorage(&FilesystemStorageConfig{
+		MountDir: filepath.Join(dir, "backup"),
+	})
+
+	err := backup.Upload(&UploadInput{Dir: "/f", Name: "a", Bytes: make([]byte, 20)})
+	if err != nil {
+		t.Fatal(err)
+	}
+	err = s.Upload(&UploadInput{Dir: "/f", Name: "a", Bytes: make([]byte, 8)})
+	if err != nil {
+		t.Fatal(err)
+	}
+	if used := usedBytes(t, s); used != 8 {
+		t.Errorf("%d bytes used, want 8", used)
+	}
+}
@@ -2,52 +2,96 @@ package storage
 
 import (
 	"bytes"
+	"encoding/base64"
+	"encoding/hex"
+	"errors"
+	"fmt"
 	"io"
-	"mindia/utils"
+	"mindia/breaker"
+	"net/url"
+	"time"
 
 	"github.com/aws/aws-sdk-go/aws"
+	"github.com/aws/aws-sdk-go/aws/awserr"
 	"github.com/aws/aws-sdk-go/aws/credentials"
 	"github.com/aws/aws-sdk-go/aws/session"
 	"github.com/aws/aws-sdk-go/service/s3"
 )
 
 type S3Object struct {
-	Key      string
-	Metadata map[string]*string
+	Key          string
+	Metadata     map[string]*string
+	LastModified time.Time
 }
 
 type S3ClientConfig struct {
-	Bucket          string `yaml:"bucket"`
-	AccessKeyId     string `yaml:"omitempty"`
-	SecretAccessKey string `yaml:"omitempty"`
-	Endpoint        string `yaml:"endpoint"`
-	Region          string `yaml:"region"`
+	Bucket           string `yaml:"bucket"`
+	AccessKeyId      string `yaml:"omitempty"`
+	SecretAccessKey  string `yaml:"omitempty"`
+	Endpoint         string `yaml:"endpoint"`
+	Region           string `yaml:"region"`
+	StorageClass     string `yaml:"storage_class,omitempty"`
+	Provider         string `yaml:"provider,omitempty"`
+	ForcePathStyle   bool   `yaml:"force_path_style,omitempty"`
+	DisableChecksums bool   `yaml:"disable_checksums,omitempty"`
+	DisableAcl       bool   `yaml:"disable_acl,omitempty"`
+
+	// Unregistered keeps the breaker of the client out of the shared registry, for ad-hoc clients.
+	Unregistered bool `yaml:"-"`
 }
 
 type S3Client struct {
 	*S3ClientConfig `yaml:",inline"`
 	s3              *s3.S3
+	breaker         *breaker.Breaker
 }
 
-func NewS3Client(config *S3ClientConfig) *S3Client {
+func NewS3Client(config *S3ClientConfig) (*S3Client, error) {
+	applyProviderQuirks(config)
+	breakerConfig := &breaker.BreakerConfig{
+		Name:      "s3 " + config.Endpoint + "/" + config.Bucket,
+		IsFailure: isServerError,
+	}
 	s3 := S3Client{
 		S3ClientConfig: config,
 	}
-	s3.createSession(config)
-	return &s3
+	if config.Unregistered {
+		s3.breaker = breaker.NewBreaker(breakerConfig)
+	} else {
+		s3.breaker = breaker.Register(breakerConfig)
+	}
+	err := s3.createSession(config)
+	if err != nil {
+		return nil, err
+	}
+	return &s3, nil
 }
 
-func (s *S3Client) createSession(config *S3ClientConfig) {
+func (s *S3Client) createSession(config *S3ClientConfig) error {
 	s3Config := &aws.Config{
 		Credentials: credentials.NewStaticCredentials(config.AccessKeyId, config.SecretAccessKey, ""),
 		Endpoint:    aws.String(config.Endpoint),
 		Region:      aws.String(config.Region),
+
+		S3ForcePathStyle:              aws.Bool(config.ForcePathStyle),
+		S3DisableContentMD5Validation: aws.Bool(config.DisableChecksums),
 	}
 	newSession, err := session.NewSession(s3Config)
 	if err != nil {
-		utils.ExitErrorf("Unable create a new session, %v", err)
+		return fmt.Errorf("unable to create a new session, %v", err)
 	}
 	s.s3 = s3.New(newSession)
+	return nil
+}
+
+// Client errors such as a missing key mean s3 is up, only server and network
+// errors count towards tripping the breaker.
+func isServerError(err error) bool {
+	var rf awserr.RequestFailure
+	if errors.As(err, &rf) {
+		return rf.StatusCode() >= 500
+	}
+	return true
 }
 
 type ListObjectsParams struct {
@@ -60,7 +104,11 @@ func (s *S3Client) ListObjects(p *ListObjectsParams) ([]S3Object, error) {
 		Bucket: aws.String(p.Bucket),
 		Prefix: aws.String(p.Prefix),
 	}
-	output, err := s.s3.ListObjectsV2(intput)
+	var output *s3.ListObjectsV2Output
+	err := s.breaker.Do(func() (err error) {
+		output, err = s.s3.ListObjectsV2(intput)
+		return err
+	})
 	if err != nil {
 		return nil, err
 	}
@@ -74,9 +122,45 @@ func (s *S3Client) ListObjects(p *ListObjectsParams) ([]S3Object, error) {
 	return objs, nil
 }
 
+type ListObjectsPageParams struct {
+	Bucket     string
+	Prefix     string
+	StartAfter string
+	MaxKeys    int64
+}
+
+func (s *S3Client) ListObjectsPage(p *ListObjectsPageParams) ([]string, bool, error) {
+	input := &s3.ListObjectsV2Input{
+		Bucket:  aws.String(p.Bucket),
+		Prefix:  aws.String(p.Prefix),
+		MaxKeys: aws.Int64(p.MaxKeys),
+	}
+	if p.StartAfter != "" {
+		input.StartAfter = aws.String(p.StartAfter)
+	}
+	var output *s3.ListObjectsV2Output
+	err := s.breaker.Do(func() (err error) {
+		output, err = s.s3.ListObjectsV2(input)
+		return err
+	})
+	if err != nil {
+		return nil, false, err
+	}
+	var keys []string
+	for _, obj := range output.Contents {
+		keys = append(keys, *obj.Key)
+	}
+	return keys, aws.BoolValue(output.IsTruncated), nil
+}
+
+var ErrObjectTooLarge = errors.New("s3 object is too large")
+
 type GetObjectParams struct {
 	Bucket string
 	Key    string
+
+	// MaxBytes makes DownloadObject fail with ErrObjectTooLarge past this size.
+	MaxBytes int64
 }
 
 func (s *S3Client) DownloadObject(p *GetObjectParams) ([]byte, error) {
@@ -84,12 +168,34 @@ func (s *S3Client) DownloadObject(p *GetObjectParams) ([]byte, error) {
 		Bucket: aws.String(p.Bucket),
 		Key:    aws.String(p.Key),
 	}
-	output, err := s.s3.GetObject(input)
-	if err != nil {
-		return nil, err
+	var (
+		body     []byte
+		tooLarge bool
+	)
+	err := s.breaker.Do(func() error {
+		output, err := s.s3.GetObject(input)
+		if err != nil {
+			return err
+		}
+		defer output.Body.Close()
+		if p.MaxBytes <= 0 {
+			body, err = io.ReadAll(output.Body)
+			return err
+		}
+
+		// an oversized object is the caller's problem, not a sign that s3 is down
+		if aws.Int64Value(output.ContentLength) > p.MaxBytes {
+			tooLarge = true
+			return nil
+		}
+		body, err = io.ReadAll(io.LimitReader(output.Body, p.MaxBytes+1))
+		tooLarge = int64(len(body)) > p.MaxBytes
+		return err
+	})
+	if err == nil && tooLarge {
+		return nil, ErrObjectTooLarge
 	}
-	defer output.Body.Close()
-	return io.ReadAll(output.Body)
+	return body, err
 }
 
 func (s *S3Client) GetObject(p *GetObjectParams) (*S3Object, error) {
@@ -97,21 +203,36 @@ func (s *S3Client) GetObject(p *GetObjectParams) (*S3Object, error) {
 		Bucket: aws.String(p.Bucket),
 		Key:    aws.String(p.Key),
 	}
-	output, err := s.s3.GetObject(input)
+	var output *s3.GetObjectOutput
+	err := s.breaker.Do(func() (err error) {
+		output, err = s.s3.GetObject(input)
+		return err
+	})
 	if err != nil {
 		return nil, err
 	}
+	output.Body.Close()
 
 	return &S3Object{
-		Key:      p.Key,
-		Metadata: output.Metadata,
+		Key:          p.Key,
+		Metadata:     output.Metadata,
+		LastModified: aws.TimeValue(output.LastModified),
 	}, nil
 }
 
+func (s *S3Client) PresignGetObject(p *GetObjectParams, expiry time.Duration) (string, error) {
+	req, _ := s.s3.GetObjectRequest(&s3.GetObjectInput{
+		Bucket: aws.String(p.Bucket),
+		Key:    aws.String(p.Key),
+	})
+	return req.Presign(expiry)
+}
+
 type PutObjectParams struct {
 	Bucket   string
 	Key      string
 	Body     []byte
+	Checksum string
 	Metadata map[string]*string
 }
 
@@ -119,12 +240,50 @@ func (s *S3Client) PutObject(p *PutObjectParams) error {
 	input := &s3.PutObjectInput{
 		Bucket:   aws.String(p.Bucket),
 		Key:      aws.String(p.Key),
-		ACL:      aws.String("public-read"),
 		Body:     bytes.NewReader(p.Body),
 		Metadata: p.Metadata,
 	}
-	_, err := s.s3.PutObject(input)
-	return err
+	if !s.DisableAcl {
+		input.ACL = aws.String("public-read")
+	}
+	if s.StorageClass != "" {
+		input.StorageClass = aws.String(s.StorageClass)
+	}
+	if p.Checksum != "" && !s.DisableChecksums {
+		sum, err := hex.DecodeString(p.Checksum)
+		if err != nil {
+			return err
+		}
+		input.ChecksumSHA256 = aws.String(base64.StdEncoding.EncodeToString(sum))
+	}
+	return s.breaker.Do(func() error {
+		_, err := s.s3.PutObject(input)
+		return err
+	})
+}
+
+type CopyObjectParams struct {
+	Bucket string
+	SrcKey string
+	DstKey string
+}
+
+func (s *S3Client) CopyObject(p *CopyObjectParams) error {
+	input := &s3.CopyObjectInput{
+		Bucket:     aws.String(p.Bucket),
+		CopySource: aws.String(url.PathEscape(p.Bucket + "/" + p.SrcKey)),
+		Key:        aws.String(p.DstKey),
+	}
+	if !s.DisableAcl {
+		input.ACL = aws.String("public-read")
+	}
+	if s.StorageClass != "" {
+		input.StorageClass = aws.String(s.StorageClass)
+	}
+	return s.breaker.Do(func() error {
+		_, err := s.s3.CopyObject(input)
+		return err
+	})
 }
 
 type DeleteObjectParams struct {
@@ -137,6 +296,8 @@ func (s *S3Client) DeleteObject(p *DeleteObjectParams) error {
 		Bucket: aws.String(p.Bucket),
 		Key:    aws.String(p.Key),
 	}
-	_, err := s.s3.DeleteObject(input)
-	return err
+	return s.breaker.Do(func() error {
+		_, err := s.s3.DeleteObject(input)
+		return err
+	})
 }
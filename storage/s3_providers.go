@@ -0,0 +1,42 @@
+package storage
+
+import "regexp"
+
+const (
+	ProviderAws   = "aws"
+	ProviderMinio = "minio"
+	ProviderR2    = "r2"
+	ProviderB2    = "b2"
+
+	defaultS3Region = "us-east-1"
+)
+
+var b2EndpointRegion = regexp.MustCompile(`s3\.([a-z0-9-]+)\.backblazeb2\.com`)
+
+// Fills the settings the s3 compatible providers need and aws doesn't,
+// explicit values in the config are left untouched.
+func applyProviderQuirks(config *S3ClientConfig) {
+	switch config.Provider {
+	case ProviderMinio:
+		config.ForcePathStyle = true
+	case ProviderR2:
+		if config.Region == "" {
+			config.Region = "auto"
+		}
+		config.DisableChecksums = true
+		config.DisableAcl = true
+		config.StorageClass = ""
+	case ProviderB2:
+		if config.Region == "" {
+			if m := b2EndpointRegion.FindStringSubmatch(config.Endpoint); m != nil {
+				config.Region = m[1]
+			}
+		}
+		config.DisableChecksums = true
+		config.DisableAcl = true
+		config.StorageClass = ""
+	}
+	if config.Region == "" {
+		config.Region = defaultS3Region
+	}
+}
@@ -1,7 +1,18 @@
 package storage
 
 import (
+	"errors"
 	"mindia/types"
+	"mindia/utils"
+	"path"
+	"sort"
+	"time"
+)
+
+var (
+	ErrNotFound            = errors.New("file not found")
+	ErrPresignNotSupported = errors.New("storage does not support presigned urls")
+	ErrChecksumMismatch    = errors.New("checksum mismatch, the stored file is corrupted")
 )
 
 type StoragesMap map[string]Storage
@@ -18,13 +29,18 @@ type Storage interface {
 	ReadOne(in *ReadOneInput) (*types.File, error)
 	ReadAll(in *ReadAllInput) ([]*types.File, error)
 	Delete(in *DeleteInput) error
+	PresignedUrl(in *PresignedUrlInput) (string, error)
+	Copy(in *CopyInput) error
+	Rename(in *RenameInput) error
+	List(in *ListInput) (*ListOutput, error)
 }
 
 type UploadInput struct {
-	Dir   string
-	Name  string
-	Bytes []byte
-	Size  types.Size
+	Dir      string
+	Name     string
+	Bytes    []byte
+	Size     types.Size
+	Checksum string
 }
 
 type DoesExistInput struct {
@@ -56,3 +72,92 @@ type DeleteInput struct {
 	Dir  string
 	Name string
 }
+
+type PresignedUrlInput struct {
+	Dir    string
+	Name   string
+	Expiry time.Duration
+}
+
+type CopyInput struct {
+	SrcDir  string
+	SrcName string
+	DstDir  string
+	DstName string
+}
+
+type RenameInput struct {
+	SrcDir  string
+	SrcName string
+	DstDir  string
+	DstName string
+}
+
+const defaultMaxKeys = 1000
+
+type ListInput struct {
+	Prefix            string
+	ContinuationToken string
+	MaxKeys           int
+}
+
+type ListOutput struct {
+	Files                 []*types.File
+	NextContinuationToken string
+}
+
+func (in *ListInput) maxKeys() int {
+	if in.MaxKeys <= 0 {
+		return defaultMaxKeys
+	}
+	return in.MaxKeys
+}
+
+func fileKey(file *types.File) string {
+	return utils.JoinPath("/", file.Dir, file.Name)
+}
+
+func newListOutput(keys []string, maxKeys int, truncated bool) *ListOutput {
+	sort.Strings(keys)
+	out := &ListOutput{}
+	for i, key := range keys {
+		if i > 0 && keys[i-1] == key {
+			continue
+		}
+		if len(out.Files) == maxKeys {
+			truncated = true
+			break
+		}
+		out.Files = append(out.Files, &types.File{
+			Dir:  path.Dir(key),
+			Name: path.Base(key),
+		})
+	}
+	if truncated && len(out.Files) > 0 {
+		out.NextContinuationToken = fileKey(out.Files[len(out.Files)-1])
+	}
+	return out
+}
+
+// mergeListOutputs stops at the smallest continuation token, since a truncated
+// output may still hold keys sorting before the ones of the others.
+func mergeListOutputs(maxKeys int, outputs ...*ListOutput) *ListOutput {
+	var (
+		keys  []string
+		limit string
+	)
+	for _, out := range outputs {
+		token := out.NextContinuationToken
+		if token != "" && (limit == "" || token < limit) {
+			limit = token
+		}
+	}
+	for _, out := range outputs {
+		for _, file := range out.Files {
+			if key := fileKey(file); limit == "" || key <= limit {
+				keys = append(keys, key)
+			}
+		}
+	}
+	return newListOutput(keys, maxKeys, limit != "")
+}
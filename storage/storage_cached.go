@@ -0,0 +1,252 @@
+package storage
+
+import (
+	"container/list"
+	"crypto/sha256"
+	"encoding/hex"
+	"mindia/types"
+	"mindia/utils"
+	"os"
+	"path/filepath"
+	"strconv"
+	"strings"
+	"sync"
+)
+
+type CachedStorageConfig struct {
+	Storage  Storage `yaml:"storage"`
+	CacheDir string  `yaml:"cache_dir"`
+	MaxBytes int64   `yaml:"max_bytes"`
+}
+
+const maxCacheGenerations = 10000
+
+type cachedEntry struct {
+	key  string
+	size int64
+}
+
+type CachedStorage struct {
+	*StorageConfig       `yaml:",inline"`
+	*CachedStorageConfig `yaml:",inline"`
+	cache                *FilesystemStorage
+	entries              map[string]*list.Element
+	lru                  *list.List
+	size                 int64
+	generations          map[string]uint64
+	clock                uint64
+	floor                uint64
+	mu                   sync.Mutex
+}
+
+func NewCachedStorage(config *CachedStorageConfig) *CachedStorage {
+	clearCacheDir(config.CacheDir)
+	return &CachedStorage{
+		StorageConfig: &StorageConfig{
+			StorageType: "cached",
+		},
+		CachedStorageConfig: config,
+		cache: NewFileSystemStorage(&FilesystemStorageConfig{
+			MountDir: config.CacheDir,
+		}),
+		entries:     map[string]*list.Element{},
+		lru:         list.New(),
+		generations: map[string]uint64{},
+	}
+}
+
+func clearCacheDir(dir string) {
+	files, err := os.ReadDir(dir)
+	if err != nil {
+		return
+	}
+	for _, f := range files {
+		name := strings.SplitN(f.Name(), ".", 2)[0]
+		if _, err := hex.DecodeString(name); err == nil && len(name) == sha256.Size*2 && !f.IsDir() {
+			os.Remove(filepath.Join(dir, f.Name()))
+		}
+	}
+}
+
+func cacheKey(dir, name string) string {
+	return hashBytes([]byte(utils.JoinPath(dir, name)))
+}
+
+func (s *CachedStorage) lookup(key string) bool {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	e, ok := s.entries[key]
+	if ok {
+		s.lru.MoveToFront(e)
+	}
+	return ok
+}
+
+// generation changes every time the key is invalidated. Keys without one share
+// the floor, which moves past every generation handed out when the map is reset.
+func (s *CachedStorage) generation(key string) uint64 {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	return s.generationLocked(key)
+}
+
+func (s *CachedStorage) generationLocked(key string) uint64 {
+	if g, ok := s.generations[key]; ok {
+		return g
+	}
+	return s.floor
+}
+
+// store caches b unless the key was invalidated since generation was read,
+// in which case b may predate the last write.
+func (s *CachedStorage) store(key string, b []byte, generation uint64) {
+	if int64(len(b)) > s.MaxBytes || s.generation(key) != generation {
+		return
+	}
+
+	// the bytes are written aside and renamed in place under the lock, so a stale
+	// download never overwrites the cached copy of a newer one
+	tmp := key + ".tmp" + strconv.FormatUint(generation, 10)
+	err := s.cache.Upload(&UploadInput{
+		Name:  tmp,
+		Bytes: b,
+	})
+	if err != nil {
+		return
+	}
+
+	s.mu.Lock()
+	if s.generationLocked(key) != generation {
+		s.mu.Unlock()
+		s.cache.Delete(&DeleteInput{Name: tmp})
+		return
+	}
+	err = s.cache.Rename(&RenameInput{
+		SrcName: tmp,
+		DstName: key,
+	})
+	if err != nil {
+		s.mu.Unlock()
+		s.cache.Delete(&DeleteInput{Name: tmp})
+		return
+	}
+	if e, ok := s.entries[key]; ok {
+		s.size -= e.Value.(*cachedEntry).size
+		s.lru.Remove(e)
+	}
+	s.entries[key] = s.lru.PushFront(&cachedEntry{
+		key:  key,
+		size: int64(len(b)),
+	})
+	s.size += int64(len(b))
+
+	var evicted []string
+	for s.size > s.MaxBytes {
+		e := s.lru.Back()
+		entry := e.Value.(*cachedEntry)
+		s.lru.Remove(e)
+		delete(s.entries, entry.key)
+		s.size -= entry.size
+		evicted = append(evicted, entry.key)
+	}
+	s.mu.Unlock()
+
+	for _, key := range evicted {
+		s.cache.Delete(&DeleteInput{Name: key})
+	}
+}
+
+func (s *CachedStorage) invalidate(dir, name string) {
+	key := cacheKey(dir, name)
+
+	s.mu.Lock()
+	if len(s.generations) >= maxCacheGenerations {
+		s.generations = map[string]uint64{}
+		s.clock++
+		s.floor = s.clock
+	}
+	s.clock++
+	s.generations[key] = s.clock
+	e, ok := s.entries[key]
+	if ok {
+		s.size -= e.Value.(*cachedEntry).size
+		s.lru.Remove(e)
+		delete(s.entries, key)
+	}
+	s.mu.Unlock()
+
+	if ok {
+		s.cache.Delete(&DeleteInput{Name: key})
+	}
+}
+
+func (s *CachedStorage) Upload(in *UploadInput) error {
+	s.invalidate(in.Dir, in.Name)
+	defer s.invalidate(in.Dir, in.Name)
+	return s.Storage.Upload(in)
+}
+
+func (s *CachedStorage) Download(in *DonwloadInput) ([]byte, error) {
+	key := cacheKey(in.Dir, in.Name)
+	if s.lookup(key) {
+		b, err := s.cache.Download(&DonwloadInput{Name: key})
+		if err == nil && b != nil {
+			return b, nil
+		}
+		s.invalidate(in.Dir, in.Name)
+	}
+
+	generation := s.generation(key)
+	b, err := s.Storage.Download(in)
+	if err != nil || b == nil {
+		return b, err
+	}
+	s.store(key, b, generation)
+	return b, nil
+}
+
+func (s *CachedStorage) DoesExist(in *DoesExistInput) (bool, error) {
+	return s.Storage.DoesExist(in)
+}
+
+func (s *CachedStorage) ReadSize(in *ReadSizeInput) (*types.Size, error) {
+	return s.Storage.ReadSize(in)
+}
+
+func (s *CachedStorage) ReadOne(in *ReadOneInput) (*types.File, error) {
+	return s.Storage.ReadOne(in)
+}
+
+func (s *CachedStorage) ReadAll(in *ReadAllInput) ([]*types.File, error) {
+	return s.Storage.ReadAll(in)
+}
+
+func (s *CachedStorage) Delete(in *DeleteInput) error {
+	s.invalidate(in.Dir, in.Name)
+	defer s.invalidate(in.Dir, in.Name)
+	return s.Storage.Delete(in)
+}
+
+func (s *CachedStorage) PresignedUrl(in *PresignedUrlInput) (string, error) {
+	return s.Storage.PresignedUrl(in)
+}
+
+func (s *CachedStorage) Copy(in *CopyInput) error {
+	s.invalidate(in.DstDir, in.DstName)
+	defer s.invalidate(in.DstDir, in.DstName)
+	return s.Storage.Copy(in)
+}
+
+func (s *CachedStorage) Rename(in *RenameInput) error {
+	s.invalidate(in.SrcDir, in.SrcName)
+	s.invalidate(in.DstDir, in.DstName)
+	defer s.invalidate(in.SrcDir, in.SrcName)
+	defer s.invalidate(in.DstDir, in.DstName)
+	return s.Storage.Rename(in)
+}
+
+func (s *CachedStorage) List(in *ListInput) (*ListOutput, error) {
+	return s.Storage.List(in)
+}
@@ -0,0 +1,297 @@
+package storage
+
+import (
+	"bytes"
+	"crypto/sha256"
+	"encoding/hex"
+	"image"
+	"mindia/types"
+	"mindia/utils"
+	"strconv"
+	"strings"
+	"sync"
+)
+
+const defaultBlobsDir = "/.blobs"
+
+type ContentAddressedStorageConfig struct {
+	Storage  Storage `yaml:"storage"`
+	BlobsDir string  `yaml:"blobs_dir"`
+}
+
+type ContentAddressedStorage struct {
+	*StorageConfig                 `yaml:",inline"`
+	*ContentAddressedStorageConfig `yaml:",inline"`
+	mu                             sync.Mutex
+}
+
+func NewContentAddressedStorage(config *ContentAddressedStorageConfig) *ContentAddressedStorage {
+	if config.BlobsDir == "" {
+		config.BlobsDir = defaultBlobsDir
+	}
+	return &ContentAddressedStorage{
+		StorageConfig: &StorageConfig{
+			StorageType: "cas",
+		},
+		ContentAddressedStorageConfig: config,
+	}
+}
+
+func hashBytes(b []byte) string {
+	sum := sha256.Sum256(b)
+	return hex.EncodeToString(sum[:])
+}
+
+func (s *ContentAddressedStorage) readRef(dir, name string) (string, error) {
+	ref, err := s.Storage.Download(&DonwloadInput{
+		Dir:  dir,
+		Name: name,
+	})
+	if err != nil || ref == nil {
+		return "", err
+	}
+	return strings.TrimSpace(string(ref)), nil
+}
+
+func (s *ContentAddressedStorage) readRefCount(hash string) (int, error) {
+	b, err := s.Storage.Download(&DonwloadInput{
+		Dir:  s.BlobsDir,
+		Name: hash + ".refs",
+	})
+	if err != nil || b == nil {
+		return 0, err
+	}
+	return strconv.Atoi(strings.TrimSpace(string(b)))
+}
+
+func (s *ContentAddressedStorage) writeRefCount(hash string, count int) error {
+	if count <= 0 {
+		err := s.Storage.Delete(&DeleteInput{
+			Dir:  s.BlobsDir,
+			Name: hash,
+		})
+		if err != nil {
+			return err
+		}
+		return s.Storage.Delete(&DeleteInput{
+			Dir:  s.BlobsDir,
+			Name: hash + ".refs",
+		})
+	}
+	return s.Storage.Upload(&UploadInput{
+		Dir:   s.BlobsDir,
+		Name:  hash + ".refs",
+		Bytes: []byte(strconv.Itoa(count)),
+	})
+}
+
+func (s *ContentAddressedStorage) release(hash string) error {
+	count, err := s.readRefCount(hash)
+	if err != nil {
+		return err
+	}
+	return s.writeRefCount(hash, count-1)
+}
+
+func (s *ContentAddressedStorage) Upload(in *UploadInput) error {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	hash := hashBytes(in.Bytes)
+
+	previous, err := s.readRef(in.Dir, in.Name)
+	if err != nil {
+		return err
+	}
+	if previous == hash {
+		return nil
+	}
+
+	count, err := s.readRefCount(hash)
+	if err != nil {
+		return err
+	}
+	if count == 0 {
+		err = s.Storage.Upload(&UploadInput{
+			Dir:   s.BlobsDir,
+			Name:  hash,
+			Bytes: in.Bytes,
+			Size:  in.Size,
+		})
+		if err != nil {
+			return err
+		}
+	}
+	err = s.writeRefCount(hash, count+1)
+	if err != nil {
+		return err
+	}
+
+	err = s.Storage.Upload(&UploadInput{
+		Dir:   in.Dir,
+		Name:  in.Name,
+		Bytes: []byte(hash),
+		Size:  in.Size,
+	})
+	if err != nil {
+		return err
+	}
+
+	if previous != "" {
+		return s.release(previous)
+	}
+	return nil
+}
+
+func (s *ContentAddressedStorage) Download(in *DonwloadInput) ([]byte, error) {
+	hash, err := s.readRef(in.Dir, in.Name)
+	if err != nil || hash == "" {
+		return nil, err
+	}
+	return s.Storage.Download(&DonwloadInput{
+		Dir:  s.BlobsDir,
+		Name: hash,
+	})
+}
+
+func (s *ContentAddressedStorage) DoesExist(in *DoesExistInput) (bool, error) {
+	return s.Storage.DoesExist(in)
+}
+
+func (s *ContentAddressedStorage) ReadSize(in *ReadSizeInput) (*types.Size, error) {
+	b, err := s.Download(&DonwloadInput{
+		Dir:  in.Dir,
+		Name: in.Name,
+	})
+	if err != nil {
+		return nil, err
+	}
+	img, _, err := image.DecodeConfig(bytes.NewReader(b))
+	if err != nil {
+		return nil, err
+	}
+	return &types.Size{
+		Width:  int32(img.Width),
+		Height: int32(img.Height),
+	}, nil
+}
+
+func (s *ContentAddressedStorage) ReadOne(in *ReadOneInput) (*types.File, error) {
+	return s.Storage.ReadOne(in)
+}
+
+func (s *ContentAddressedStorage) ReadAll(in *ReadAllInput) ([]*types.File, error) {
+	if utils.JoinPath(in.Dir) == utils.JoinPath(s.BlobsDir) {
+		return nil, nil
+	}
+	return s.Storage.ReadAll(in)
+}
+
+func (s *ContentAddressedStorage) Delete(in *DeleteInput) error {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	hash, err := s.readRef(in.Dir, in.Name)
+	if err != nil {
+		return err
+	}
+	err = s.Storage.Delete(in)
+	if err != nil {
+		return err
+	}
+	if hash == "" {
+		return nil
+	}
+	return s.release(hash)
+}
+
+func (s *ContentAddressedStorage) PresignedUrl(in *PresignedUrlInput) (string, error) {
+	hash, err := s.readRef(in.Dir, in.Name)
+	if err != nil {
+		return "", err
+	}
+	if hash == "" {
+		return "", ErrNotFound
+	}
+	return s.Storage.PresignedUrl(&PresignedUrlInput{
+		Dir:    s.BlobsDir,
+		Name:   hash,
+		Expiry: in.Expiry,
+	})
+}
+
+func (s *ContentAddressedStorage) Copy(in *CopyInput) error {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	hash, err := s.readRef(in.SrcDir, in.SrcName)
+	if err != nil {
+		return err
+	}
+	if hash == "" {
+		return ErrNotFound
+	}
+	previous, err := s.readRef(in.DstDir, in.DstName)
+	if err != nil {
+		return err
+	}
+	if previous == hash {
+		return nil
+	}
+
+	count, err := s.readRefCount(hash)
+	if err != nil {
+		return err
+	}
+	err = s.writeRefCount(hash, count+1)
+	if err != nil {
+		return err
+	}
+	err = s.Storage.Upload(&UploadInput{
+		Dir:   in.DstDir,
+		Name:  in.DstName,
+		Bytes: []byte(hash),
+	})
+	if err != nil {
+		return err
+	}
+
+	if previous != "" {
+		return s.release(previous)
+	}
+	return nil
+}
+
+func (s *ContentAddressedStorage) Rename(in *RenameInput) error {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	previous, err := s.readRef(in.DstDir, in.DstName)
+	if err != nil {
+		return err
+	}
+	err = s.Storage.Rename(in)
+	if err != nil {
+		return err
+	}
+	if previous != "" {
+		return s.release(previous)
+	}
+	return nil
+}
+
+func (s *ContentAddressedStorage) List(in *ListInput) (*ListOutput, error) {
+	out, err := s.Storage.List(in)
+	if err != nil {
+		return nil, err
+	}
+	blobsDir := utils.JoinPath("/", s.BlobsDir)
+	files := out.Files[:0]
+	for _, file := range out.Files {
+		if utils.JoinPath("/", file.Dir) != blobsDir {
+			files = append(files, file)
+		}
+	}
+	out.Files = files
+	return out, nil
+}
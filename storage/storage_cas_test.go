@@ -0,0 +1,105 @@
+package storage
+
+import (
+	"errors"
+	"testing"
+)
+
+func newTestCas(t *testing.T) *ContentAddressedStorage {
+	return NewContentAddressedStorage(&ContentAddressedStorageConfig{
+		Storage: NewFileSystemStorage(&FilesystemStorageConfig{
+			MountDir: t.TempDir(),
+		}),
+	})
+}
+
+func TestContentAddressedStorageRefCounts(t *testing.T) {
+	upload := func(name, content string) func(s *ContentAddressedStorage) error {
+		return func(s *ContentAddressedStorage) error {
+			return s.Upload(&UploadInput{Dir: "/f", Name: name, Bytes: []byte(content)})
+		}
+	}
+	remove := func(name string) func(s *ContentAddressedStorage) error {
+		return func(s *ContentAddressedStorage) error {
+			return s.Delete(&DeleteInput{Dir: "/f", Name: name})
+		}
+	}
+	copyTo := func(src, dst string) func(s *ContentAddressedStorage) error {
+		return func(s *ContentAddressedStorage) error {
+			return s.Copy(&CopyInput{SrcDir: "/f", SrcName: src, DstDir: "/f", DstName: dst})
+		}
+	}
+	rename := func(src, dst string) func(s *ContentAddressedStorage) error {
+		return func(s *ContentAddressedStorage) error {
+			return s.Rename(&RenameInput{SrcDir: "/f", SrcName: src, DstDir: "/f", DstName: dst})
+		}
+	}
+
+	// each step runs on the state left by the previous ones
+	steps := []struct {
+		name string
+		do   func(s *ContentAddressedStorage) error
+		refs map[string]int
+	}{
+		{"first upload", upload("a", "x"), map[string]int{"x": 1}},
+		{"same content", upload("b", "x"), map[string]int{"x": 2}},
+		{"same file again", upload("b", "x"), map[string]int{"x": 2}},
+		{"overwrite", upload("a", "y"), map[string]int{"x": 1, "y": 1}},
+		{"copy", copyTo("a", "c"), map[string]int{"x": 1, "y": 2}},
+		{"copy over a reference", copyTo("b", "c"), map[string]int{"x": 2, "y": 1}},
+		{"rename over a reference", rename("c", "a"), map[string]int{"x": 2, "y": 0}},
+		{"delete", remove("b"), map[string]int{"x": 1}},
+		{"delete the last reference", remove("a"), map[string]int{"x": 0}},
+	}
+
+	s := newTestCas(t)
+	for _, step := range steps {
+		err := step.do(s)
+		if err != nil {
+			t.Fatalf("%s: %s", step.name, err)
+		}
+		for content, want := range step.refs {
+			hash := hashBytes([]byte(content))
+			count, err := s.readRefCount(hash)
+			if err != nil {
+				t.Fatalf("%s: %s", step.name, err)
+			}
+			if count != want {
+				t.Errorf("%s: %s has %d references, want %d", step.name, content, count, want)
+			}
+			blob, err := s.Storage.Download(&DonwloadInput{Dir: s.BlobsDir, Name: hash})
+			if err != nil {
+				t.Fatalf("%s: %s", step.name, err)
+			}
+			if (blob != nil) != (want > 0) {
+				t.Errorf("%s: blob of %s exists %t with %d references", step.name, content, blob != nil, want)
+			}
+		}
+	}
+}
+
+func TestContentAddressedStorageDownload(t *testing.T) {
+	s := newTestCas(t)
+	err := s.Upload(&UploadInput{Dir: "/f", Name: "a", Bytes: []byte("x")})
+	if err != nil {
+		t.Fatal(err)
+	}
+
+	b, err := s.Download(&DonwloadInput{Dir: "/f", Name: "a"})
+	if err != nil || string(b) != "x" {
+		t.Errorf("got %q and %v, want the uploaded content", b, err)
+	}
+	b, err = s.Download(&DonwloadInput{Dir: "/f", Name: "missing"})
+	if err != nil || b != nil {
+		t.Errorf("got %q and %v for a missing file", b, err)
+	}
+	err = s.Copy(&CopyInput{SrcDir: "/f", SrcName: "missing", DstDir: "/f", DstName: "b"})
+	if !errors.Is(err, ErrNotFound) {
+		t.Errorf("got error %v copying a missing file, want %v", err, ErrNotFound)
+	}
+
+	files, err := s.ReadAll(&ReadAllInput{Dir: s.BlobsDir})
+	if err != nil || len(files) != 0 {
+		t.Errorf("blobs should not be listed, got %d files and %v", len(files), err)
+	}
+}
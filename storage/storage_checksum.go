@@ -0,0 +1,159 @@
+package storage
+
+import (
+	"mindia/types"
+	"mindia/utils"
+	"strings"
+)
+
+const defaultChecksumsDir = "/.checksums"
+
+type ChecksumStorageConfig struct {
+	Storage         Storage `yaml:"storage"`
+	ChecksumsDir    string  `yaml:"checksums_dir"`
+	VerifyDownloads bool    `yaml:"verify_downloads"`
+}
+
+type ChecksumStorage struct {
+	*StorageConfig         `yaml:",inline"`
+	*ChecksumStorageConfig `yaml:",inline"`
+}
+
+func NewChecksumStorage(config *ChecksumStorageConfig) *ChecksumStorage {
+	if config.ChecksumsDir == "" {
+		config.ChecksumsDir = defaultChecksumsDir
+	}
+	return &ChecksumStorage{
+		StorageConfig: &StorageConfig{
+			StorageType: "checksum",
+		},
+		ChecksumStorageConfig: config,
+	}
+}
+
+func (s *ChecksumStorage) checksumDir(dir string) string {
+	return utils.JoinPath(s.ChecksumsDir, dir)
+}
+
+func (s *ChecksumStorage) ReadChecksum(dir, name string) (string, error) {
+	b, err := s.Storage.Download(&DonwloadInput{
+		Dir:  s.checksumDir(dir),
+		Name: name,
+	})
+	if err != nil || b == nil {
+		return "", err
+	}
+	return strings.TrimSpace(string(b)), nil
+}
+
+func (s *ChecksumStorage) Upload(in *UploadInput) error {
+	checksum := hashBytes(in.Bytes)
+	err := s.Storage.Upload(&UploadInput{
+		Dir:      in.Dir,
+		Name:     in.Name,
+		Bytes:    in.Bytes,
+		Size:     in.Size,
+		Checksum: checksum,
+	})
+	if err != nil {
+		return err
+	}
+	return s.Storage.Upload(&UploadInput{
+		Dir:   s.checksumDir(in.Dir),
+		Name:  in.Name,
+		Bytes: []byte(checksum),
+	})
+}
+
+func (s *ChecksumStorage) Download(in *DonwloadInput) ([]byte, error) {
+	b, err := s.Storage.Download(in)
+	if err != nil || b == nil || !s.VerifyDownloads {
+		return b, err
+	}
+
+	checksum, err := s.ReadChecksum(in.Dir, in.Name)
+	if err != nil {
+		return nil, err
+	}
+	if checksum != "" && hashBytes(b) != checksum {
+		return nil, ErrChecksumMismatch
+	}
+	return b, nil
+}
+
+func (s *ChecksumStorage) DoesExist(in *DoesExistInput) (bool, error) {
+	return s.Storage.DoesExist(in)
+}
+
+func (s *ChecksumStorage) ReadSize(in *ReadSizeInput) (*types.Size, error) {
+	return s.Storage.ReadSize(in)
+}
+
+func (s *ChecksumStorage) ReadOne(in *ReadOneInput) (*types.File, error) {
+	return s.Storage.ReadOne(in)
+}
+
+func (s *ChecksumStorage) ReadAll(in *ReadAllInput) ([]*types.File, error) {
+	return s.Storage.ReadAll(in)
+}
+
+func (s *ChecksumStorage) Delete(in *DeleteInput) error {
+	err := s.Storage.Delete(in)
+	if err != nil {
+		return err
+	}
+	s.Storage.Delete(&DeleteInput{
+		Dir:  s.checksumDir(in.Dir),
+		Name: in.Name,
+	})
+	return nil
+}
+
+func (s *ChecksumStorage) PresignedUrl(in *PresignedUrlInput) (string, error) {
+	return s.Storage.PresignedUrl(in)
+}
+
+func (s *ChecksumStorage) Copy(in *CopyInput) error {
+	err := s.Storage.Copy(in)
+	if err != nil {
+		return err
+	}
+	s.Storage.Copy(&CopyInput{
+		SrcDir:  s.checksumDir(in.SrcDir),
+		SrcName: in.SrcName,
+		DstDir:  s.checksumDir(in.DstDir),
+		DstName: in.DstName,
+	})
+	return nil
+}
+
+func (s *ChecksumStorage) Rename(in *RenameInput) error {
+	err := s.Storage.Rename(in)
+	if err != nil {
+		return err
+	}
+	s.Storage.Rename(&RenameInput{
+		SrcDir:  s.checksumDir(in.SrcDir),
+		SrcName: in.SrcName,
+		DstDir:  s.checksumDir(in.DstDir),
+		DstName: in.DstName,
+	})
+	return nil
+}
+
+func (s *ChecksumStorage) List(in *ListInput) (*ListOutput, error) {
+	out, err := s.Storage.List(in)
+	if err != nil {
+		return nil, err
+	}
+	checksumsDir := utils.JoinPath("/", s.ChecksumsDir)
+	files := out.Files[:0]
+	for _, file := range out.Files {
+		dir := utils.JoinPath("/", file.Dir)
+		if dir != checksumsDir && !strings.HasPrefix(dir, checksumsDir+"/") {
+			files = append(files, file)
+		}
+	}
+	out.Files = files
+	return out, nil
+}
@@ -32,3 +32,19 @@ func (s *DropboxStorage) ReadAll(in *ReadAllInput) ([]*types.File, error) {
 func (s *DropboxStorage) Delete(in *DeleteInput) error {
 	return nil
 }
+
+func (s *DropboxStorage) PresignedUrl(in *PresignedUrlInput) (string, error) {
+	return "", ErrPresignNotSupported
+}
+
+func (s *DropboxStorage) Copy(in *CopyInput) error {
+	return nil
+}
+
+func (s *DropboxStorage) Rename(in *RenameInput) error {
+	return nil
+}
+
+func (s *DropboxStorage) List(in *ListInput) (*ListOutput, error) {
+	return &ListOutput{}, nil
+}
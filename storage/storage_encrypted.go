@@ -0,0 +1,193 @@
+package storage
+
+import (
+	"bytes"
+	"crypto/aes"
+	"crypto/cipher"
+	"crypto/rand"
+	"encoding/hex"
+	"errors"
+	"image"
+	"io"
+	"mindia/types"
+	"mindia/utils"
+)
+
+var (
+	ErrInvalidKey       = errors.New("encryption key must be 32 bytes hex encoded")
+	ErrInvalidEncrypted = errors.New("file is not encrypted with the expected format")
+)
+
+var encryptedMagic = []byte("MDE1")
+
+const dataKeySize = 32
+
+type EncryptedStorageConfig struct {
+	Storage Storage `yaml:"storage"`
+	Key     string  `yaml:"-"`
+}
+
+type EncryptedStorage struct {
+	*StorageConfig          `yaml:",inline"`
+	*EncryptedStorageConfig `yaml:",inline"`
+	masterKey               cipher.AEAD
+}
+
+func NewEncryptedStorage(config *EncryptedStorageConfig) *EncryptedStorage {
+	masterKey, err := newAead(config.Key)
+	if err != nil {
+		utils.ExitErrorf("Unable to create encrypted storage, %v", err)
+	}
+	return &EncryptedStorage{
+		StorageConfig: &StorageConfig{
+			StorageType: "encrypted",
+		},
+		EncryptedStorageConfig: config,
+		masterKey:              masterKey,
+	}
+}
+
+func newAead(hexKey string) (cipher.AEAD, error) {
+	key, err := hex.DecodeString(hexKey)
+	if err != nil || len(key) != dataKeySize {
+		return nil, ErrInvalidKey
+	}
+	return aeadFromKey(key)
+}
+
+func aeadFromKey(key []byte) (cipher.AEAD, error) {
+	block, err := aes.NewCipher(key)
+	if err != nil {
+		return nil, err
+	}
+	return cipher.NewGCM(block)
+}
+
+func seal(aead cipher.AEAD, plaintext []byte) ([]byte, error) {
+	nonce := make([]byte, aead.NonceSize())
+	if _, err := io.ReadFull(rand.Reader, nonce); err != nil {
+		return nil, err
+	}
+	return aead.Seal(nonce, nonce, plaintext, nil), nil
+}
+
+func open(aead cipher.AEAD, sealed []byte) ([]byte, error) {
+	if len(sealed) < aead.NonceSize() {
+		return nil, ErrInvalidEncrypted
+	}
+	return aead.Open(nil, sealed[:aead.NonceSize()], sealed[aead.NonceSize():], nil)
+}
+
+func (s *EncryptedStorage) encrypt(plaintext []byte) ([]byte, error) {
+	dataKey := make([]byte, dataKeySize)
+	if _, err := io.ReadFull(rand.Reader, dataKey); err != nil {
+		return nil, err
+	}
+	wrappedKey, err := seal(s.masterKey, dataKey)
+	if err != nil {
+		return nil, err
+	}
+
+	aead, err := aeadFromKey(dataKey)
+	if err != nil {
+		return nil, err
+	}
+	ciphertext, err := seal(aead, plaintext)
+	if err != nil {
+		return nil, err
+	}
+
+	out := make([]byte, 0, len(encryptedMagic)+len(wrappedKey)+len(ciphertext))
+	out = append(out, encryptedMagic...)
+	out = append(out, wrappedKey...)
+	return append(out, ciphertext...), nil
+}
+
+func (s *EncryptedStorage) decrypt(b []byte) ([]byte, error) {
+	wrappedKeySize := s.masterKey.NonceSize() + dataKeySize + s.masterKey.Overhead()
+	if !bytes.HasPrefix(b, encryptedMagic) || len(b) < len(encryptedMagic)+wrappedKeySize {
+		return nil, ErrInvalidEncrypted
+	}
+	b = b[len(encryptedMagic):]
+
+	dataKey, err := open(s.masterKey, b[:wrappedKeySize])
+	if err != nil {
+		return nil, err
+	}
+	aead, err := aeadFromKey(dataKey)
+	if err != nil {
+		return nil, err
+	}
+	return open(aead, b[wrappedKeySize:])
+}
+
+func (s *EncryptedStorage) Upload(in *UploadInput) error {
+	ciphertext, err := s.encrypt(in.Bytes)
+	if err != nil {
+		return err
+	}
+	return s.Storage.Upload(&UploadInput{
+		Dir:   in.Dir,
+		Name:  in.Name,
+		Bytes: ciphertext,
+		Size:  in.Size,
+	})
+}
+
+func (s *EncryptedStorage) Download(in *DonwloadInput) ([]byte, error) {
+	b, err := s.Storage.Download(in)
+	if err != nil || b == nil {
+		return b, err
+	}
+	return s.decrypt(b)
+}
+
+func (s *EncryptedStorage) DoesExist(in *DoesExistInput) (bool, error) {
+	return s.Storage.DoesExist(in)
+}
+
+func (s *EncryptedStorage) ReadSize(in *ReadSizeInput) (*types.Size, error) {
+	b, err := s.Download(&DonwloadInput{
+		Dir:  in.Dir,
+		Name: in.Name,
+	})
+	if err != nil {
+		return nil, err
+	}
+	img, _, err := image.DecodeConfig(bytes.NewReader(b))
+	if err != nil {
+		return nil, err
+	}
+	return &types.Size{
+		Width:  int32(img.Width),
+		Height: int32(img.Height),
+	}, nil
+}
+
+func (s *EncryptedStorage) ReadOne(in *ReadOneInput) (*types.File, error) {
+	return s.Storage.ReadOne(in)
+}
+
+func (s *EncryptedStorage) ReadAll(in *ReadAllInput) ([]*types.File, error) {
+	return s.Storage.ReadAll(in)
+}
+
+func (s *EncryptedStorage) Delete(in *DeleteInput) error {
+	return s.Storage.Delete(in)
+}
+
+func (s *EncryptedStorage) PresignedUrl(in *PresignedUrlInput) (string, error) {
+	return "", ErrPresignNotSupported
+}
+
+func (s *EncryptedStorage) Copy(in *CopyInput) error {
+	return s.Storage.Copy(in)
+}
+
+func (s *EncryptedStorage) Rename(in *RenameInput) error {
+	return s.Storage.Rename(in)
+}
+
+func (s *EncryptedStorage) List(in *ListInput) (*ListOutput, error) {
+	return s.Storage.List(in)
+}
@@ -6,20 +6,30 @@ import (
 	_ "image/gif"
 	_ "image/jpeg"
 	_ "image/png"
+	"io"
+	"io/fs"
 	"mindia/types"
 	"mindia/utils"
 	"os"
+	"path"
 	"path/filepath"
 	"strings"
+	"sync"
 )
 
 type FilesystemStorageConfig struct {
-	MountDir string `yaml:"mount_dir"`
+	MountDir        string `yaml:"mount_dir"`
+	MaxBytes        int64  `yaml:"max_bytes,omitempty"`
+	EvictTransforms bool   `yaml:"evict_transforms,omitempty"`
 }
 
 type FilesystemStorage struct {
 	*StorageConfig           `yaml:",inline"`
 	*FilesystemStorageConfig `yaml:",inline"`
+	used                     int64
+	usageLoaded              bool
+	onEvict                  []func(dir, name string)
+	mu                       sync.Mutex
 }
 
 func NewFileSystemStorage(config *FilesystemStorageConfig) *FilesystemStorage {
@@ -29,6 +39,7 @@ func NewFileSystemStorage(config *FilesystemStorageConfig) *FilesystemStorage {
 		},
 		FilesystemStorageConfig: config,
 	}
+	registerMountDir(config.MountDir)
 	s.createMountPathNotExists("")
 	return s
 }
@@ -42,7 +53,28 @@ func (s *FilesystemStorage) createMountPathNotExists(dir string) {
 
 func (s *FilesystemStorage) Upload(in *UploadInput) error {
 	s.createMountPathNotExists(in.Dir)
-	return os.WriteFile(utils.JoinPath(s.MountDir, in.Dir, in.Name), in.Bytes, 0777)
+	p := utils.JoinPath(s.MountDir, in.Dir, in.Name)
+	err := s.reserve(p, int64(len(in.Bytes)))
+	if err != nil {
+		return err
+	}
+	err = os.WriteFile(p, in.Bytes, 0777)
+	if err != nil {
+		s.resetUsage()
+		return err
+	}
+	if in.Checksum == "" {
+		return nil
+	}
+
+	written, err := os.ReadFile(p)
+	if err != nil {
+		return err
+	}
+	if hashBytes(written) != in.Checksum {
+		return ErrChecksumMismatch
+	}
+	return nil
 }
 
 func (s *FilesystemStorage) Download(in *DonwloadInput) ([]byte, error) {
@@ -84,13 +116,14 @@ func (s *FilesystemStorage) ReadSize(in *ReadSizeInput) (*types.Size, error) {
 
 func (s *FilesystemStorage) ReadOne(in *ReadOneInput) (*types.File, error) {
 	s.createMountPathNotExists(in.Dir)
-	_, err := os.ReadFile(utils.JoinPath(s.MountDir, in.Dir, in.Name))
+	info, err := os.Stat(utils.JoinPath(s.MountDir, in.Dir, in.Name))
 	if err != nil {
 		return nil, err
 	}
 	return &types.File{
-		Dir:  in.Dir,
-		Name: in.Name,
+		Dir:     in.Dir,
+		Name:    in.Name,
+		ModTime: info.ModTime(),
 	}, nil
 }
 
@@ -102,6 +135,9 @@ func (s *FilesystemStorage) ReadAll(in *ReadAllInput) ([]*types.File, error) {
 	}
 	var files2 []*types.File
 	for _, f := range files {
+		if f.IsDir() {
+			continue
+		}
 		if in.Prefix != "" {
 			if !strings.HasPrefix(f.Name(), in.Prefix) {
 				continue
@@ -117,5 +153,90 @@ func (s *FilesystemStorage) ReadAll(in *ReadAllInput) ([]*types.File, error) {
 
 func (s *FilesystemStorage) Delete(in *DeleteInput) error {
 	s.createMountPathNotExists(in.Dir)
-	return os.Remove(filepath.Join(s.MountDir, in.Dir, in.Name))
+	p := filepath.Join(s.MountDir, in.Dir, in.Name)
+	s.release(p)
+	err := os.Remove(p)
+	if err != nil {
+		s.resetUsage()
+	}
+	return err
+}
+
+func (s *FilesystemStorage) PresignedUrl(in *PresignedUrlInput) (string, error) {
+	return "", ErrPresignNotSupported
+}
+
+func (s *FilesystemStorage) Copy(in *CopyInput) error {
+	s.createMountPathNotExists(in.DstDir)
+
+	srcPath := utils.JoinPath(s.MountDir, in.SrcDir, in.SrcName)
+	dstPath := utils.JoinPath(s.MountDir, in.DstDir, in.DstName)
+	src, err := os.Open(srcPath)
+	if err != nil {
+		return err
+	}
+	defer src.Close()
+
+	err = s.reserve(dstPath, fileSize(srcPath))
+	if err != nil {
+		return err
+	}
+	dst, err := os.OpenFile(dstPath, os.O_CREATE|os.O_WRONLY|os.O_TRUNC, 0777)
+	if err != nil {
+		s.resetUsage()
+		return err
+	}
+	_, err = io.Copy(dst, src)
+	if err != nil {
+		dst.Close()
+		s.resetUsage()
+		return err
+	}
+	return dst.Close()
+}
+
+func (s *FilesystemStorage) Rename(in *RenameInput) error {
+	s.createMountPathNotExists(in.DstDir)
+	dstPath := utils.JoinPath(s.MountDir, in.DstDir, in.DstName)
+	s.release(dstPath)
+	err := os.Rename(utils.JoinPath(s.MountDir, in.SrcDir, in.SrcName), dstPath)
+	if err != nil {
+		s.resetUsage()
+	}
+	return err
+}
+
+func (s *FilesystemStorage) List(in *ListInput) (*ListOutput, error) {
+	prefix := in.Prefix
+	if !strings.HasPrefix(prefix, "/") {
+		prefix = "/" + prefix
+	}
+	dir := prefix
+	if !strings.HasSuffix(dir, "/") {
+		dir = path.Dir(dir)
+	}
+	root := filepath.Join(s.MountDir, dir)
+
+	var keys []string
+	err := filepath.WalkDir(root, func(p string, d fs.DirEntry, err error) error {
+		if err != nil {
+			return err
+		}
+		if d.IsDir() {
+			return nil
+		}
+		rel, err := filepath.Rel(s.MountDir, p)
+		if err != nil {
+			return err
+		}
+		key := utils.JoinPath("/", rel)
+		if strings.HasPrefix(key, prefix) && key > in.ContinuationToken {
+			keys = append(keys, key)
+		}
+		return nil
+	})
+	if err != nil && !errors.Is(err, os.ErrNotExist) {
+		return nil, err
+	}
+	return newListOutput(keys, in.maxKeys(), false), nil
 }
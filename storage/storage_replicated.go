@@ -0,0 +1,347 @@
+package storage
+
+import (
+	"errors"
+	"fmt"
+	"mindia/types"
+	"sync"
+	"time"
+)
+
+const (
+	defaultReplicationQueueSize = 1000
+	defaultReconcileInterval    = time.Hour
+	maxReplicationAttempts      = 5
+)
+
+var ErrReplicationQueueFull = errors.New("replication queue is full, the replica will be reconciled later")
+
+type ReplicatedStorageConfig struct {
+	Primary           Storage       `yaml:"primary"`
+	Replica           Storage       `yaml:"replica"`
+	QueueSize         int           `yaml:"queue_size,omitempty"`
+	ReconcileInterval time.Duration `yaml:"reconcile_interval,omitempty"`
+}
+
+type ReplicaHealth struct {
+	PrimaryHealthy  bool      `json:"primary_healthy"`
+	ReplicaHealthy  bool      `json:"replica_healthy"`
+	PrimaryFailures int       `json:"primary_failures"`
+	ReplicaFailures int       `json:"replica_failures"`
+	Pending         int       `json:"pending"`
+	LastError       string    `json:"last_error,omitempty"`
+	LastReconcile   time.Time `json:"last_reconcile"`
+	Reconciled      int       `json:"reconciled"`
+}
+
+type replicationOp struct {
+	apply func(s Storage) error
+}
+
+type ReplicatedStorage struct {
+	*StorageConfig           `yaml:",inline"`
+	*ReplicatedStorageConfig `yaml:",inline"`
+	queue                    chan *replicationOp
+	health                   ReplicaHealth
+	replicaOnly              map[string]bool
+	mu                       sync.Mutex
+}
+
+func NewReplicatedStorage(config *ReplicatedStorageConfig) *ReplicatedStorage {
+	if config.QueueSize <= 0 {
+		config.QueueSize = defaultReplicationQueueSize
+	}
+	if config.ReconcileInterval <= 0 {
+		config.ReconcileInterval = defaultReconcileInterval
+	}
+	s := &ReplicatedStorage{
+		StorageConfig: &StorageConfig{
+			StorageType: "replicated",
+		},
+		ReplicatedStorageConfig: config,
+		queue:                   make(chan *replicationOp, config.QueueSize),
+		replicaOnly:             map[string]bool{},
+		health: ReplicaHealth{
+			PrimaryHealthy: true,
+			ReplicaHealthy: true,
+		},
+	}
+	go s.replicate()
+	go s.scheduleReconcile()
+	return s
+}
+
+func (s *ReplicatedStorage) Health() *ReplicaHealth {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	health := s.health
+	health.Pending = len(s.queue)
+	return &health
+}
+
+func (s *ReplicatedStorage) record(primary bool, err error) {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	healthy, failures := &s.health.ReplicaHealthy, &s.health.ReplicaFailures
+	if primary {
+		healthy, failures = &s.health.PrimaryHealthy, &s.health.PrimaryFailures
+	}
+	if err == nil {
+		*healthy = true
+		*failures = 0
+		return
+	}
+	*healthy = false
+	*failures++
+	s.health.LastError = err.Error()
+}
+
+func (s *ReplicatedStorage) enqueue(op *replicationOp) {
+	select {
+	case s.queue <- op:
+	default:
+		s.record(false, ErrReplicationQueueFull)
+	}
+}
+
+// replicate retries each op before moving to the next one, so that the writes
+// to a key reach the replica in the order they reached the primary.
+func (s *ReplicatedStorage) replicate() {
+	for op := range s.queue {
+		for attempts := 1; ; attempts++ {
+			err := op.apply(s.Replica)
+			s.record(false, err)
+			if err == nil || attempts >= maxReplicationAttempts {
+				break
+			}
+			time.Sleep(time.Duration(attempts) * time.Second)
+		}
+	}
+}
+
+func (s *ReplicatedStorage) scheduleReconcile() {
+	for {
+		time.Sleep(s.ReconcileInterval)
+		err := s.Reconcile()
+		if err != nil {
+			fmt.Printf("Error: %s", err)
+		}
+	}
+}
+
+func listKeys(st Storage) (map[string]*types.File, error) {
+	keys := map[string]*types.File{}
+	in := &ListInput{}
+	for {
+		out, err := st.List(in)
+		if err != nil {
+			return nil, err
+		}
+		for _, file := range out.Files {
+			keys[fileKey(file)] = file
+		}
+		if out.NextContinuationToken == "" {
+			return keys, nil
+		}
+		in.ContinuationToken = out.NextContinuationToken
+	}
+}
+
+func copyFile(from, to Storage, file *types.File) error {
+	b, err := from.Download(&DonwloadInput{
+		Dir:  file.Dir,
+		Name: file.Name,
+	})
+	if err != nil || b == nil {
+		return err
+	}
+	return to.Upload(&UploadInput{
+		Dir:   file.Dir,
+		Name:  file.Name,
+		Bytes: b,
+	})
+}
+
+// Reconcile copies the keys missing from the replica. Keys missing from the primary
+// are copied back only when they were written to the replica while the primary was
+// failing, any other replica-only key is a delete and must not be resurrected.
+func (s *ReplicatedStorage) Reconcile() error {
+	primary, err := listKeys(s.Primary)
+	s.record(true, err)
+	if err != nil {
+		return err
+	}
+	replica, err := listKeys(s.Replica)
+	s.record(false, err)
+	if err != nil {
+		return err
+	}
+
+	reconciled := 0
+	for key, file := range primary {
+		if _, ok := replica[key]; !ok {
+			if err := copyFile(s.Primary, s.Replica, file); err == nil {
+				reconciled++
+			}
+		}
+	}
+	for key, file := range replica {
+		if _, ok := primary[key]; ok || !s.isReplicaOnly(key) {
+			continue
+		}
+		if err := copyFile(s.Replica, s.Primary, file); err == nil {
+			s.clearReplicaOnly(key)
+			reconciled++
+		}
+	}
+
+	s.mu.Lock()
+	s.health.LastReconcile = time.Now()
+	s.health.Reconciled += reconciled
+	s.mu.Unlock()
+	return nil
+}
+
+func replicatedKey(dir, name string) string {
+	return fileKey(&types.File{Dir: dir, Name: name})
+}
+
+func (s *ReplicatedStorage) isReplicaOnly(key string) bool {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+	return s.replicaOnly[key]
+}
+
+func (s *ReplicatedStorage) clearReplicaOnly(key string) {
+	s.mu.Lock()
+	delete(s.replicaOnly, key)
+	s.mu.Unlock()
+}
+
+func (s *ReplicatedStorage) Upload(in *UploadInput) error {
+	err := s.Primary.Upload(in)
+	s.record(true, err)
+	if err != nil {
+		err = s.Replica.Upload(in)
+		s.record(false, err)
+		if err == nil {
+			s.mu.Lock()
+			s.replicaOnly[replicatedKey(in.Dir, in.Name)] = true
+			s.mu.Unlock()
+		}
+		return err
+	}
+	s.clearReplicaOnly(replicatedKey(in.Dir, in.Name))
+	s.enqueue(&replicationOp{apply: func(st Storage) error {
+		return st.Upload(in)
+	}})
+	return nil
+}
+
+func (s *ReplicatedStorage) Download(in *DonwloadInput) ([]byte, error) {
+	b, err := s.Primary.Download(in)
+	s.record(true, err)
+	if err == nil {
+		return b, nil
+	}
+	b, err = s.Replica.Download(in)
+	s.record(false, err)
+	return b, err
+}
+
+func (s *ReplicatedStorage) DoesExist(in *DoesExistInput) (bool, error) {
+	exists, err := s.Primary.DoesExist(in)
+	if err == nil {
+		return exists, nil
+	}
+	return s.Replica.DoesExist(in)
+}
+
+func (s *ReplicatedStorage) ReadSize(in *ReadSizeInput) (*types.Size, error) {
+	size, err := s.Primary.ReadSize(in)
+	if err == nil {
+		return size, nil
+	}
+	return s.Replica.ReadSize(in)
+}
+
+func (s *ReplicatedStorage) ReadOne(in *ReadOneInput) (*types.File, error) {
+	file, err := s.Primary.ReadOne(in)
+	if err == nil {
+		return file, nil
+	}
+	return s.Replica.ReadOne(in)
+}
+
+func (s *ReplicatedStorage) ReadAll(in *ReadAllInput) ([]*types.File, error) {
+	files, err := s.Primary.ReadAll(in)
+	s.record(true, err)
+	if err == nil {
+		return files, nil
+	}
+	return s.Replica.ReadAll(in)
+}
+
+func (s *ReplicatedStorage) Delete(in *DeleteInput) error {
+	if key := replicatedKey(in.Dir, in.Name); s.isReplicaOnly(key) {
+		err := s.Replica.Delete(in)
+		s.record(false, err)
+		if err == nil {
+			s.clearReplicaOnly(key)
+		}
+		return err
+	}
+
+	err := s.Primary.Delete(in)
+	s.record(true, err)
+	if err != nil {
+		return err
+	}
+	s.enqueue(&replicationOp{apply: func(st Storage) error {
+		return st.Delete(in)
+	}})
+	return nil
+}
+
+func (s *ReplicatedStorage) PresignedUrl(in *PresignedUrlInput) (string, error) {
+	url, err := s.Primary.PresignedUrl(in)
+	if err == nil || err == ErrPresignNotSupported {
+		return url, err
+	}
+	return s.Replica.PresignedUrl(in)
+}
+
+func (s *ReplicatedStorage) Copy(in *CopyInput) error {
+	err := s.Primary.Copy(in)
+	s.record(true, err)
+	if err != nil {
+		return err
+	}
+	s.enqueue(&replicationOp{apply: func(st Storage) error {
+		return st.Copy(in)
+	}})
+	return nil
+}
+
+func (s *ReplicatedStorage) Rename(in *RenameInput) error {
+	err := s.Primary.Rename(in)
+	s.record(true, err)
+	if err != nil {
+		return err
+	}
+	s.enqueue(&replicationOp{apply: func(st Storage) error {
+		return st.Rename(in)
+	}})
+	return nil
+}
+
+func (s *ReplicatedStorage) List(in *ListInput) (*ListOutput, error) {
+	out, err := s.Primary.List(in)
+	s.record(true, err)
+	if err == nil {
+		return out, nil
+	}
+	return s.Replica.List(in)
+}
@@ -21,11 +21,15 @@ type S3Storage struct {
 }
 
 func NewS3Storage(config *S3StorageConfig) *S3Storage {
+	client, err := NewS3Client(config.S3ClientConfig)
+	if err != nil {
+		utils.ExitErrorf("Unable to create the s3 client, %v", err)
+	}
 	return &S3Storage{
 		StorageConfig: &StorageConfig{
 			StorageType: "s3",
 		},
-		s3: NewS3Client(config.S3ClientConfig),
+		s3: client,
 	}
 }
 
@@ -43,9 +47,10 @@ func (s *S3Storage) Upload(in *UploadInput) error {
 	height := strconv.Itoa(int(in.Size.Height))
 
 	return s.s3.PutObject(&PutObjectParams{
-		Bucket: s.s3.Bucket,
-		Key:    utils.JoinPath(in.Dir, in.Name),
-		Body:   in.Bytes,
+		Bucket:   s.s3.Bucket,
+		Key:      utils.JoinPath(in.Dir, in.Name),
+		Body:     in.Bytes,
+		Checksum: in.Checksum,
 		Metadata: map[string]*string{
 			"width":  &width,
 			"height": &height,
@@ -97,7 +102,7 @@ func (s *S3Storage) DoesExist(in *DoesExistInput) (bool, error) {
 }
 
 func (s *S3Storage) ReadOne(in *ReadOneInput) (*types.File, error) {
-	_, err := s.s3.GetObject(&GetObjectParams{
+	obj, err := s.s3.GetObject(&GetObjectParams{
 		Bucket: s.s3.Bucket,
 		Key:    utils.JoinPath(in.Dir, in.Name),
 	})
@@ -105,8 +110,9 @@ func (s *S3Storage) ReadOne(in *ReadOneInput) (*types.File, error) {
 		return nil, err
 	}
 	return &types.File{
-		Dir:  in.Dir,
-		Name: in.Name,
+		Dir:     in.Dir,
+		Name:    in.Name,
+		ModTime: obj.LastModified,
 	}, nil
 }
 
@@ -132,9 +138,58 @@ func (s *S3Storage) ReadAll(in *ReadAllInput) ([]*types.File, error) {
 	return files, nil
 }
 
+func (s *S3Storage) PresignedUrl(in *PresignedUrlInput) (string, error) {
+	return s.s3.PresignGetObject(&GetObjectParams{
+		Bucket: s.s3.Bucket,
+		Key:    utils.JoinPath(in.Dir, in.Name),
+	}, in.Expiry)
+}
+
 func (s *S3Storage) Delete(in *DeleteInput) error {
 	return s.s3.DeleteObject(&DeleteObjectParams{
 		Bucket: s.s3.Bucket,
 		Key:    utils.JoinPath(in.Dir, in.Name),
 	})
 }
+
+// The copy source isn't cleaned by the sdk like request paths are, so the keys
+// must not start with a slash.
+func (s *S3Storage) Copy(in *CopyInput) error {
+	return s.s3.CopyObject(&CopyObjectParams{
+		Bucket: s.s3.Bucket,
+		SrcKey: strings.TrimPrefix(utils.JoinPath(in.SrcDir, in.SrcName), "/"),
+		DstKey: strings.TrimPrefix(utils.JoinPath(in.DstDir, in.DstName), "/"),
+	})
+}
+
+func (s *S3Storage) Rename(in *RenameInput) error {
+	err := s.Copy(&CopyInput{
+		SrcDir:  in.SrcDir,
+		SrcName: in.SrcName,
+		DstDir:  in.DstDir,
+		DstName: in.DstName,
+	})
+	if err != nil {
+		return err
+	}
+	return s.Delete(&DeleteInput{
+		Dir:  in.SrcDir,
+		Name: in.SrcName,
+	})
+}
+
+func (s *S3Storage) List(in *ListInput) (*ListOutput, error) {
+	keys, truncated, err := s.s3.ListObjectsPage(&ListObjectsPageParams{
+		Bucket:     s.s3.Bucket,
+		Prefix:     strings.TrimPrefix(in.Prefix, "/"),
+		StartAfter: strings.TrimPrefix(in.ContinuationToken, "/"),
+		MaxKeys:    int64(in.maxKeys()),
+	})
+	if err != nil {
+		return nil, err
+	}
+	for i, key := range keys {
+		keys[i] = utils.JoinPath("/", key)
+	}
+	return newListOutput(keys, in.maxKeys(), truncated), nil
+}
@@ -0,0 +1,79 @@
+package storage
+
+import (
+	"io"
+	"net/http"
+	"strings"
+	"testing"
+
+	"github.com/aws/aws-sdk-go/aws/request"
+)
+
+// newTestS3Storage returns a storage whose requests are handed to send instead
+// of being sent over the network.
+func newTestS3Storage(t *testing.T, send func(r *http.Request)) *S3Storage {
+	client, err := NewS3Client(&S3ClientConfig{
+		Bucket:          "bucket",
+		AccessKeyId:     "key",
+		SecretAccessKey: "secret",
+		Endpoint:        "http://127.0.0.1:9000",
+		Region:          "us-east-1",
+		ForcePathStyle:  true,
+		Unregistered:    true,
+	})
+	if err != nil {
+		t.Fatal(err)
+	}
+	client.s3.Handlers.Send.Clear()
+	client.s3.Handlers.Send.PushBack(func(r *request.Request) {
+		send(r.HTTPRequest)
+		r.HTTPResponse = &http.Response{
+			StatusCode: http.StatusOK,
+			Header:     http.Header{},
+			Body:       io.NopCloser(strings.NewReader("<CopyObjectResult></CopyObjectResult>")),
+		}
+	})
+	return &S3Storage{
+		StorageConfig: &StorageConfig{StorageType: "s3"},
+		s3:            client,
+	}
+}
+
+func TestS3Copy(t *testing.T) {
+	tests := []struct {
+		name   string
+		in     *CopyInput
+		source string
+		path   string
+	}{
+		{
+			name:   "rooted dirs",
+			in:     &CopyInput{SrcDir: "/houses", SrcName: "a.jpg", DstDir: "/gardens", DstName: "b.jpg"},
+			source: "bucket%2Fhouses%2Fa.jpg",
+			path:   "/bucket/gardens/b.jpg",
+		},
+		{
+			name:   "relative dirs",
+			in:     &CopyInput{SrcDir: "houses", SrcName: "a.jpg", DstDir: "gardens", DstName: "b.jpg"},
+			source: "bucket%2Fhouses%2Fa.jpg",
+			path:   "/bucket/gardens/b.jpg",
+		},
+	}
+	for _, test := range tests {
+		var source, path string
+		s := newTestS3Storage(t, func(r *http.Request) {
+			source = r.Header.Get("X-Amz-Copy-Source")
+			path = r.URL.Path
+		})
+		err := s.Copy(test.in)
+		if err != nil {
+			t.Fatalf("%s: %s", test.name, err)
+		}
+		if source != test.source {
+			t.Errorf("%s: got copy source %q, want %q", test.name, source, test.source)
+		}
+		if path != test.path {
+			t.Errorf("%s: got path %q, want %q", test.name, path, test.path)
+		}
+	}
+}
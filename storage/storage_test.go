@@ -0,0 +1,106 @@
+package storage
+
+import (
+	"mindia/types"
+	"reflect"
+	"testing"
+)
+
+func listKeys(out *ListOutput) []string {
+	keys := []string{}
+	for _, file := range out.Files {
+		keys = append(keys, fileKey(file))
+	}
+	return keys
+}
+
+func listOutput(token string, keys ...string) *ListOutput {
+	out := &ListOutput{NextContinuationToken: token}
+	for _, key := range keys {
+		out.Files = append(out.Files, &types.File{
+			Dir:  "/a",
+			Name: key,
+		})
+	}
+	return out
+}
+
+func TestNewListOutput(t *testing.T) {
+	tests := []struct {
+		name      string
+		keys      []string
+		maxKeys   int
+		truncated bool
+		want      []string
+		token     string
+	}{
+		{"empty", nil, 10, false, []string{}, ""},
+		{"sorted", []string{"/a/c", "/a/a", "/a/b"}, 10, false, []string{"/a/a", "/a/b", "/a/c"}, ""},
+		{"deduplicated", []string{"/a/b", "/a/a", "/a/b"}, 10, false, []string{"/a/a", "/a/b"}, ""},
+		{"truncated by max keys", []string{"/a/c", "/a/a", "/a/b"}, 2, false, []string{"/a/a", "/a/b"}, "/a/b"},
+		{"exactly max keys", []string{"/a/a", "/a/b"}, 2, false, []string{"/a/a", "/a/b"}, ""},
+		{"already truncated", []string{"/a/a"}, 10, true, []string{"/a/a"}, "/a/a"},
+	}
+	for _, test := range tests {
+		out := newListOutput(test.keys, test.maxKeys, test.truncated)
+		if got := listKeys(out); !reflect.DeepEqual(got, test.want) {
+			t.Errorf("%s: got keys %v, want %v", test.name, got, test.want)
+		}
+		if out.NextContinuationToken != test.token {
+			t.Errorf("%s: got token %q, want %q", test.name, out.NextContinuationToken, test.token)
+		}
+	}
+}
+
+func TestMergeListOutputs(t *testing.T) {
+	tests := []struct {
+		name    string
+		outputs []*ListOutput
+		maxKeys int
+		want    []string
+		token   string
+	}{
+		{
+			name:    "interleaved",
+			outputs: []*ListOutput{listOutput("", "a", "c"), listOutput("", "b", "d")},
+			maxKeys: 10,
+			want:    []string{"/a/a", "/a/b", "/a/c", "/a/d"},
+		},
+		{
+			name:    "shared keys",
+			outputs: []*ListOutput{listOutput("", "a", "b"), listOutput("", "b")},
+			maxKeys: 10,
+			want:    []string{"/a/a", "/a/b"},
+		},
+		{
+			name:    "max keys",
+			outputs: []*ListOutput{listOutput("", "a", "c"), listOutput("", "b", "d")},
+			maxKeys: 3,
+			want:    []string{"/a/a", "/a/b", "/a/c"},
+			token:   "/a/c",
+		},
+		{
+			name:    "stops at the truncated output",
+			outputs: []*ListOutput{listOutput("/a/b", "a", "b"), listOutput("", "c", "d")},
+			maxKeys: 10,
+			want:    []string{"/a/a", "/a/b"},
+			token:   "/a/b",
+		},
+		{
+			name:    "smallest token wins",
+			outputs: []*ListOutput{listOutput("/a/d", "b", "d"), listOutput("/a/c", "a", "c")},
+			maxKeys: 10,
+			want:    []string{"/a/a", "/a/b", "/a/c"},
+			token:   "/a/c",
+		},
+	}
+	for _, test := range tests {
+		out := mergeListOutputs(test.maxKeys, test.outputs...)
+		if got := listKeys(out); !reflect.DeepEqual(got, test.want) {
+			t.Errorf("%s: got keys %v, want %v", test.name, got, test.want)
+		}
+		if out.NextContinuationToken != test.token {
+			t.Errorf("%s: got token %q, want %q", test.name, out.NextContinuationToken, test.token)
+		}
+	}
+}
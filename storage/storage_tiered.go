@@ -0,0 +1,375 @@
+package storage
+
+import (
+	"fmt"
+	"hash/fnv"
+	"mindia/types"
+	"mindia/utils"
+	"sort"
+	"sync"
+	"time"
+)
+
+const (
+	TierHot  = "hot"
+	TierCold = "cold"
+
+	defaultLifecycleInterval = time.Hour
+	tieredKeyLocks           = 64
+)
+
+type TieredStorageConfig struct {
+	Hot               Storage       `yaml:"hot"`
+	Cold              Storage       `yaml:"cold"`
+	ColdAfter         time.Duration `yaml:"cold_after"`
+	LifecycleInterval time.Duration `yaml:"lifecycle_interval,omitempty"`
+}
+
+type tieredFile struct {
+	dir        string
+	name       string
+	tier       string
+	lastAccess time.Time
+}
+
+type TieredStorage struct {
+	*StorageConfig       `yaml:",inline"`
+	*TieredStorageConfig `yaml:",inline"`
+	files                map[string]*tieredFile
+	keyLocks             [tieredKeyLocks]sync.Mutex
+	mu                   sync.Mutex
+}
+
+func NewTieredStorage(config *TieredStorageConfig) *TieredStorage {
+	if config.LifecycleInterval <= 0 {
+		config.LifecycleInterval = defaultLifecycleInterval
+	}
+	s := &TieredStorage{
+		StorageConfig: &StorageConfig{
+			StorageType: "tiered",
+		},
+		TieredStorageConfig: config,
+		files:               map[string]*tieredFile{},
+	}
+	if config.ColdAfter > 0 {
+		go s.runLifecycle()
+	}
+	return s
+}
+
+// lockKeys serializes the writes to the given keys with the moves between
+// tiers, keys share a lock when they hash to the same one.
+func (s *TieredStorage) lockKeys(keys ...string) func() {
+	var indexes []int
+	for _, key := range keys {
+		h := fnv.New32a()
+		h.Write([]byte(key))
+		indexes = append(indexes, int(h.Sum32()%tieredKeyLocks))
+	}
+	sort.Ints(indexes)
+
+	var locked []*sync.Mutex
+	for i, index := range indexes {
+		if i > 0 && index == indexes[i-1] {
+			continue
+		}
+		s.keyLocks[index].Lock()
+		locked = append(locked, &s.keyLocks[index])
+	}
+	return func() {
+		for _, l := range locked {
+			l.Unlock()
+		}
+	}
+}
+
+func (s *TieredStorage) touch(dir, name, tier string, access bool) {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	key := utils.JoinPath(dir, name)
+	f, ok := s.files[key]
+	if !ok {
+		f = &tieredFile{
+			dir:        dir,
+			name:       name,
+			lastAccess: time.Now(),
+		}
+		s.files[key] = f
+	}
+	f.tier = tier
+	if access {
+		f.lastAccess = time.Now()
+	}
+}
+
+func (s *TieredStorage) forget(dir, name string) {
+	s.mu.Lock()
+	delete(s.files, utils.JoinPath(dir, name))
+	s.mu.Unlock()
+}
+
+func (s *TieredStorage) Tier(dir, name string) string {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	if f, ok := s.files[utils.JoinPath(dir, name)]; ok {
+		return f.tier
+	}
+	return ""
+}
+
+func (s *TieredStorage) Upload(in *UploadInput) error {
+	defer s.lockKeys(utils.JoinPath(in.Dir, in.Name))()
+
+	err := s.Hot.Upload(in)
+	if err != nil {
+		return err
+	}
+	if s.Tier(in.Dir, in.Name) == TierCold {
+		s.Cold.Delete(&DeleteInput{
+			Dir:  in.Dir,
+			Name: in.Name,
+		})
+	}
+	s.touch(in.Dir, in.Name, TierHot, true)
+	return nil
+}
+
+func (s *TieredStorage) Download(in *DonwloadInput) ([]byte, error) {
+	b, err := s.Hot.Download(in)
+	if err != nil {
+		return nil, err
+	}
+	if b != nil {
+		s.touch(in.Dir, in.Name, TierHot, true)
+		return b, nil
+	}
+
+	b, err = s.Cold.Download(in)
+	if err != nil || b == nil {
+		return b, err
+	}
+	err = s.rehydrate(in.Dir, in.Name, b)
+	if err != nil {
+		fmt.Printf("Error: %s", err)
+	}
+	return b, nil
+}
+
+func (s *TieredStorage) rehydrate(dir, name string, b []byte) error {
+	defer s.lockKeys(utils.JoinPath(dir, name))()
+
+	// a concurrent upload may have written newer bytes to the hot tier
+	exists, err := s.Hot.DoesExist(&DoesExistInput{
+		Dir:  dir,
+		Name: name,
+	})
+	if err == nil && exists {
+		return nil
+	}
+
+	err = s.Hot.Upload(&UploadInput{
+		Dir:   dir,
+		Name:  name,
+		Bytes: b,
+	})
+	if err != nil {
+		s.touch(dir, name, TierCold, true)
+		return err
+	}
+	s.touch(dir, name, TierHot, true)
+	return s.Cold.Delete(&DeleteInput{
+		Dir:  dir,
+		Name: name,
+	})
+}
+
+func (s *TieredStorage) DoesExist(in *DoesExistInput) (bool, error) {
+	exists, err := s.Hot.DoesExist(in)
+	if err == nil && exists {
+		return true, nil
+	}
+	return s.Cold.DoesExist(in)
+}
+
+func (s *TieredStorage) ReadSize(in *ReadSizeInput) (*types.Size, error) {
+	if s.Tier(in.Dir, in.Name) == TierCold {
+		return s.Cold.ReadSize(in)
+	}
+	size, err := s.Hot.ReadSize(in)
+	if err != nil {
+		return s.Cold.ReadSize(in)
+	}
+	return size, nil
+}
+
+func (s *TieredStorage) ReadOne(in *ReadOneInput) (*types.File, error) {
+	if s.Tier(in.Dir, in.Name) == TierCold {
+		return s.Cold.ReadOne(in)
+	}
+	file, err := s.Hot.ReadOne(in)
+	if err != nil {
+		return s.Cold.ReadOne(in)
+	}
+	return file, nil
+}
+
+func (s *TieredStorage) ReadAll(in *ReadAllInput) ([]*types.File, error) {
+	hot, err := s.Hot.ReadAll(in)
+	if err != nil {
+		return nil, err
+	}
+	cold, err := s.Cold.ReadAll(in)
+	if err != nil {
+		return nil, err
+	}
+
+	var files []*types.File
+	seen := map[string]bool{}
+	for _, file := range hot {
+		seen[file.Name] = true
+		s.touch(in.Dir, file.Name, TierHot, false)
+		files = append(files, file)
+	}
+	for _, file := range cold {
+		if seen[file.Name] {
+			continue
+		}
+		s.touch(in.Dir, file.Name, TierCold, false)
+		files = append(files, file)
+	}
+	return files, nil
+}
+
+func (s *TieredStorage) Delete(in *DeleteInput) error {
+	defer s.lockKeys(utils.JoinPath(in.Dir, in.Name))()
+
+	hotErr := s.Hot.Delete(in)
+	coldErr := s.Cold.Delete(in)
+	s.forget(in.Dir, in.Name)
+	if hotErr != nil && coldErr != nil {
+		return hotErr
+	}
+	return nil
+}
+
+func (s *TieredStorage) PresignedUrl(in *PresignedUrlInput) (string, error) {
+	if s.Tier(in.Dir, in.Name) == TierCold {
+		return s.Cold.PresignedUrl(in)
+	}
+	return s.Hot.PresignedUrl(in)
+}
+
+func (s *TieredStorage) runLifecycle() {
+	for range time.Tick(s.LifecycleInterval) {
+		s.transition()
+	}
+}
+
+func (s *TieredStorage) transition() {
+	threshold := time.Now().Add(-s.ColdAfter)
+
+	s.mu.Lock()
+	var idle []*tieredFile
+	for _, f := range s.files {
+		if f.tier == TierHot && f.lastAccess.Before(threshold) {
+			idle = append(idle, &tieredFile{
+				dir:  f.dir,
+				name: f.name,
+			})
+		}
+	}
+	s.mu.Unlock()
+
+	for _, f := range idle {
+		err := s.moveToCold(f.dir, f.name)
+		if err != nil {
+			fmt.Printf("Error: %s", err)
+		}
+	}
+}
+
+func (s *TieredStorage) moveToCold(dir, name string) error {
+	defer s.lockKeys(utils.JoinPath(dir, name))()
+
+	b, err := s.Hot.Download(&DonwloadInput{
+		Dir:  dir,
+		Name: name,
+	})
+	if err != nil {
+		return err
+	}
+	if b == nil {
+		s.forget(dir, name)
+		return nil
+	}
+
+	err = s.Cold.Upload(&UploadInput{
+		Dir:   dir,
+		Name:  name,
+		Bytes: b,
+	})
+	if err != nil {
+		return err
+	}
+	err = s.Hot.Delete(&DeleteInput{
+		Dir:  dir,
+		Name: name,
+	})
+	if err != nil {
+		return err
+	}
+	s.touch(dir, name, TierCold, false)
+	return nil
+}
+
+func (s *TieredStorage) tierStorage(tier string) Storage {
+	if tier == TierCold {
+		return s.Cold
+	}
+	return s.Hot
+}
+
+func (s *TieredStorage) Copy(in *CopyInput) error {
+	defer s.lockKeys(utils.JoinPath(in.SrcDir, in.SrcName), utils.JoinPath(in.DstDir, in.DstName))()
+
+	tier := s.Tier(in.SrcDir, in.SrcName)
+	err := s.tierStorage(tier).Copy(in)
+	if err != nil {
+		return err
+	}
+	if tier == "" {
+		tier = TierHot
+	}
+	s.touch(in.DstDir, in.DstName, tier, true)
+	return nil
+}
+
+func (s *TieredStorage) Rename(in *RenameInput) error {
+	defer s.lockKeys(utils.JoinPath(in.SrcDir, in.SrcName), utils.JoinPath(in.DstDir, in.DstName))()
+
+	tier := s.Tier(in.SrcDir, in.SrcName)
+	err := s.tierStorage(tier).Rename(in)
+	if err != nil {
+		return err
+	}
+	if tier == "" {
+		tier = TierHot
+	}
+	s.forget(in.SrcDir, in.SrcName)
+	s.touch(in.DstDir, in.DstName, tier, true)
+	return nil
+}
+
+func (s *TieredStorage) List(in *ListInput) (*ListOutput, error) {
+	hot, err := s.Hot.List(in)
+	if err != nil {
+		return nil, err
+	}
+	cold, err := s.Cold.List(in)
+	if err != nil {
+		return nil, err
+	}
+	return mergeListOutputs(in.maxKeys(), hot, cold), nil
+}
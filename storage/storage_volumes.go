@@ -0,0 +1,238 @@
+package storage
+
+import (
+	"errors"
+	"mindia/types"
+	"mindia/utils"
+	"os"
+	"sync"
+)
+
+var ErrNoSpaceLeft = errors.New("no volume has enough free space left")
+
+type VolumeStorageConfig struct {
+	MountDirs    []string `yaml:"mount_dirs"`
+	MinFreeBytes uint64   `yaml:"min_free_bytes"`
+}
+
+type VolumeStorage struct {
+	*StorageConfig       `yaml:",inline"`
+	*VolumeStorageConfig `yaml:",inline"`
+	volumes              []*FilesystemStorage
+	registry             map[string]*FilesystemStorage
+	mu                   sync.Mutex
+}
+
+type VolumeUsage struct {
+	MountDir  string `json:"mount_dir"`
+	FreeBytes uint64 `json:"free_bytes"`
+}
+
+func NewVolumeStorage(config *VolumeStorageConfig) *VolumeStorage {
+	s := &VolumeStorage{
+		StorageConfig: &StorageConfig{
+			StorageType: "volumes",
+		},
+		VolumeStorageConfig: config,
+		registry:            map[string]*FilesystemStorage{},
+	}
+	for _, dir := range config.MountDirs {
+		s.volumes = append(s.volumes, NewFileSystemStorage(&FilesystemStorageConfig{
+			MountDir: dir,
+		}))
+	}
+	if len(s.volumes) == 0 {
+		utils.ExitErrorf("Unable to create volume storage, no mount dir given")
+	}
+	return s
+}
+
+func (s *VolumeStorage) Usage() []*VolumeUsage {
+	var usage []*VolumeUsage
+	for _, v := range s.volumes {
+		free, _ := freeBytes(v.MountDir)
+		usage = append(usage, &VolumeUsage{
+			MountDir:  v.MountDir,
+			FreeBytes: free,
+		})
+	}
+	return usage
+}
+
+func (s *VolumeStorage) locate(dir, name string) *FilesystemStorage {
+	key := utils.JoinPath(dir, name)
+
+	s.mu.Lock()
+	v, ok := s.registry[key]
+	s.mu.Unlock()
+	if ok {
+		return v
+	}
+
+	for _, v := range s.volumes {
+		if _, err := os.Stat(utils.JoinPath(v.MountDir, dir, name)); err == nil {
+			s.mu.Lock()
+			s.registry[key] = v
+			s.mu.Unlock()
+			return v
+		}
+	}
+	return nil
+}
+
+func (s *VolumeStorage) place(size int) (*FilesystemStorage, error) {
+	var (
+		best     *FilesystemStorage
+		bestFree uint64
+	)
+	for _, v := range s.volumes {
+		free, err := freeBytes(v.MountDir)
+		if err != nil {
+			continue
+		}
+		if free < s.MinFreeBytes+uint64(size) {
+			continue
+		}
+		if best == nil || free > bestFree {
+			best = v
+			bestFree = free
+		}
+	}
+	if best == nil {
+		return nil, ErrNoSpaceLeft
+	}
+	return best, nil
+}
+
+func (s *VolumeStorage) Upload(in *UploadInput) error {
+	v := s.locate(in.Dir, in.Name)
+	if v == nil {
+		var err error
+		v, err = s.place(len(in.Bytes))
+		if err != nil {
+			return err
+		}
+	}
+
+	err := v.Upload(in)
+	if err != nil {
+		return err
+	}
+
+	s.mu.Lock()
+	s.registry[utils.JoinPath(in.Dir, in.Name)] = v
+	s.mu.Unlock()
+	return nil
+}
+
+func (s *VolumeStorage) Download(in *DonwloadInput) ([]byte, error) {
+	v := s.locate(in.Dir, in.Name)
+	if v == nil {
+		return nil, nil
+	}
+	return v.Download(in)
+}
+
+func (s *VolumeStorage) DoesExist(in *DoesExistInput) (bool, error) {
+	return s.locate(in.Dir, in.Name) != nil, nil
+}
+
+func (s *VolumeStorage) ReadSize(in *ReadSizeInput) (*types.Size, error) {
+	v := s.locate(in.Dir, in.Name)
+	if v == nil {
+		v = s.volumes[0]
+	}
+	return v.ReadSize(in)
+}
+
+func (s *VolumeStorage) ReadOne(in *ReadOneInput) (*types.File, error) {
+	v := s.locate(in.Dir, in.Name)
+	if v == nil {
+		v = s.volumes[0]
+	}
+	return v.ReadOne(in)
+}
+
+func (s *VolumeStorage) ReadAll(in *ReadAllInput) ([]*types.File, error) {
+	var files []*types.File
+	seen := map[string]bool{}
+	for _, v := range s.volumes {
+		res, err := v.ReadAll(in)
+		if err != nil {
+			return nil, err
+		}
+		for _, file := range res {
+			if seen[file.Name] {
+				continue
+			}
+			seen[file.Name] = true
+			files = append(files, file)
+		}
+	}
+	return files, nil
+}
+
+func (s *VolumeStorage) Delete(in *DeleteInput) error {
+	v := s.locate(in.Dir, in.Name)
+	if v == nil {
+		v = s.volumes[0]
+	}
+	err := v.Delete(in)
+	if err != nil {
+		return err
+	}
+
+	s.mu.Lock()
+	delete(s.registry, utils.JoinPath(in.Dir, in.Name))
+	s.mu.Unlock()
+	return nil
+}
+
+func (s *VolumeStorage) PresignedUrl(in *PresignedUrlInput) (string, error) {
+	return "", ErrPresignNotSupported
+}
+
+func (s *VolumeStorage) Copy(in *CopyInput) error {
+	v := s.locate(in.SrcDir, in.SrcName)
+	if v == nil {
+		return ErrNotFound
+	}
+	err := v.Copy(in)
+	if err != nil {
+		return err
+	}
+
+	s.mu.Lock()
+	s.registry[utils.JoinPath(in.DstDir, in.DstName)] = v
+	s.mu.Unlock()
+	return nil
+}
+
+func (s *VolumeStorage) Rename(in *RenameInput) error {
+	v := s.locate(in.SrcDir, in.SrcName)
+	if v == nil {
+		return ErrNotFound
+	}
+	err := v.Rename(in)
+	if err != nil {
+		return err
+	}
+
+	s.mu.Lock()
+	delete(s.registry, utils.JoinPath(in.SrcDir, in.SrcName))
+	s.registry[utils.JoinPath(in.DstDir, in.DstName)] = v
+	s.mu.Unlock()
+	return nil
+}
+
+func (s *VolumeStorage) List(in *ListInput) (*ListOutput, error) {
+	var outputs []*ListOutput
+	for _, v := range s.volumes {
+		out, err := v.List(in)
+		if err != nil {
+			return nil, err
+		}
+		outputs = append(outputs, out)
+	}
+	return mergeListOutputs(in.maxKeys(), outputs...), nil
+}
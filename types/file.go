@@ -4,6 +4,7 @@ import (
 	"fmt"
 	"mindia/utils"
 	"path/filepath"
+	"time"
 
 	"github.com/google/uuid"
 )
@@ -13,12 +14,29 @@ type Size struct {
 	Height int32 `json:"height"`
 }
 
+type Dimensions struct {
+	Size
+	RawWidth    int32 `json:"raw_width"`
+	RawHeight   int32 `json:"raw_height"`
+	Orientation int   `json:"orientation"`
+}
+
+type Rect struct {
+	X      int32 `json:"x"`
+	Y      int32 `json:"y"`
+	Width  int32 `json:"width"`
+	Height int32 `json:"height"`
+}
+
 type File struct {
 	Dir           string   `json:"dir"`
 	Name          string   `json:"name"`
 	ContentType   string   `json:"content_type"`
 	ContentLength int64    `json:"content_length"`
 	Children      []string `json:"children"`
+
+	// ModTime is only set by ReadOne, zero when the storage doesn't tell.
+	ModTime time.Time `json:"-"`
 }
 
 type Image struct {
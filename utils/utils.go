@@ -30,6 +30,20 @@ func NameWithoutExt(Name string) string {
 	return Name[:len(Name)-len(filepath.Ext(Name))]
 }
 
+func MinInt(a, b int) int {
+	if a < b {
+		return a
+	}
+	return b
+}
+
+func MaxInt(a, b int) int {
+	if a > b {
+		return a
+	}
+	return b
+}
+
 func ToArray[T any](mp map[string]*T) []*T {
 	var arr []*T
 	for _, p := range mp {